@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use my_azure_page_blob::{get_emulator_connection_string, PageBlobEmulator};
+use tokio::net::TcpListener;
+
+const USAGE: &str = "Usage: page-blob-emulator [--addr 127.0.0.1:10000] [--data-dir <path>]";
+
+#[tokio::main]
+async fn main() {
+    let mut addr = "127.0.0.1:10000".to_string();
+    let mut data_dir = None;
+
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--addr", Some(value)) => addr = value,
+            ("--data-dir", Some(value)) => data_dir = Some(PathBuf::from(value)),
+            _ => {
+                eprintln!("{}", USAGE);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let emulator = match data_dir {
+        Some(data_dir) => PageBlobEmulator::new_on_disk(data_dir).unwrap(),
+        None => PageBlobEmulator::new_in_memory(),
+    };
+
+    let listener = TcpListener::bind(addr.as_str()).await.unwrap();
+    let local_addr = listener.local_addr().unwrap();
+
+    println!("Page blob emulator is listening at {}", local_addr);
+    println!(
+        "Connection string: {}",
+        get_emulator_connection_string(local_addr)
+    );
+
+    emulator.run(listener).await.unwrap();
+}
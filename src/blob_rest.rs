@@ -0,0 +1,784 @@
+use std::{collections::BTreeMap, ops::Range, sync::Arc, time::SystemTime};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use bytes::Bytes;
+use hmac::{Hmac, KeyInit, Mac};
+use my_azure_storage_sdk::{
+    page_blob::consts::BLOB_PAGE_SIZE, AzureStorageConnection, AzureStorageError,
+};
+use reqwest::Method;
+use sha2::Sha256;
+
+use crate::{
+    PageBlobError, PageBlobInfo, PageBlobsSegment, SequenceNumberAction, SequenceNumberCondition,
+};
+
+const API_VERSION: &str = "2021-08-06";
+const METADATA_HEADER_PREFIX: &str = "x-ms-meta-";
+
+// Signed requests to the Blob REST API for the operations my-azure-storage-sdk does not expose.
+// The account and the endpoint come from the sdk connection, so both share one configuration
+pub(crate) struct BlobRestConnection {
+    connection: Arc<AzureStorageConnection>,
+    client: reqwest::Client,
+}
+
+impl BlobRestConnection {
+    pub fn new(connection: Arc<AzureStorageConnection>) -> Self {
+        Self {
+            connection,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn get_blob_endpoint(&self) -> &str {
+        self.connection
+            .get_connection_info()
+            .blobs_api_url
+            .trim_end_matches('/')
+    }
+
+    pub(crate) async fn execute(
+        &self,
+        request: BlobRestRequest,
+    ) -> Result<BlobRestResponse, PageBlobError> {
+        let date = httpdate::fmt_http_date(SystemTime::now());
+
+        let mut headers = request.headers;
+        headers.push(("x-ms-date".to_string(), date));
+        headers.push(("x-ms-version".to_string(), API_VERSION.to_string()));
+
+        let authorization = self.get_authorization(
+            &request.method,
+            &request.path,
+            &request.query,
+            &headers,
+            request.body.len(),
+        );
+        headers.push(("Authorization".to_string(), authorization));
+
+        let mut url = format!("{}{}", self.get_blob_endpoint(), request.path);
+
+        for (index, (name, value)) in request.query.iter().enumerate() {
+            url.push(if index == 0 { '?' } else { '&' });
+            url.push_str(name);
+            url.push('=');
+            url.push_str(&encode_uri_component(value, false));
+        }
+
+        let mut request_builder = self.client.request(request.method, url);
+
+        for (name, value) in &headers {
+            request_builder = request_builder.header(name.as_str(), value.as_str());
+        }
+
+        // Failures to send or to read are connection errors and are retried as IO errors
+        let response = request_builder
+            .body(request.body)
+            .send()
+            .await
+            .map_err(|err| {
+                std::io::Error::other(format!("Blob REST request is failed. Err: {}", err))
+            })?;
+
+        let status = response.status().as_u16();
+
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.as_str().to_lowercase(),
+                    value.to_str().unwrap_or_default().to_string(),
+                )
+            })
+            .collect();
+
+        let body = response.bytes().await.map_err(|err| {
+            std::io::Error::other(format!("Blob REST response is not read. Err: {}", err))
+        })?;
+
+        let response = BlobRestResponse {
+            status,
+            headers,
+            body,
+        };
+
+        // 304 is only returned to conditional reads, their callers check the status
+        if !(200..300).contains(&status) && status != 304 {
+            return Err(response.into_error());
+        }
+
+        Ok(response)
+    }
+
+    fn get_authorization(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &[(&'static str, String)],
+        headers: &[(String, String)],
+        content_length: usize,
+    ) -> String {
+        let connection_info = self.connection.get_connection_info();
+
+        let string_to_sign = get_string_to_sign(
+            &connection_info.account_name,
+            get_endpoint_path(self.get_blob_endpoint()),
+            method,
+            path,
+            query,
+            headers,
+            content_length,
+        );
+
+        format!(
+            "SharedKey {}:{}",
+            connection_info.account_name,
+            sign(&connection_info.account_key, &string_to_sign)
+        )
+    }
+
+    pub(crate) async fn get_blob_properties(
+        &self,
+        container_name: &str,
+        blob_name: &str,
+    ) -> Result<BlobRestProperties, PageBlobError> {
+        let response = self
+            .execute(BlobRestRequest::new(
+                Method::HEAD,
+                container_name,
+                Some(blob_name),
+            ))
+            .await?;
+
+        response.get_blob_properties()
+    }
+
+    // Returns None if the blob still has the ETag
+    pub(crate) async fn get_blob_properties_if_none_match(
+        &self,
+        container_name: &str,
+        blob_name: &str,
+        etag: &str,
+    ) -> Result<Option<BlobRestProperties>, PageBlobError> {
+        let request = BlobRestRequest::new(Method::HEAD, container_name, Some(blob_name))
+            .with_header("If-None-Match", etag);
+
+        let response = self.execute(request).await?;
+
+        if response.status == 304 {
+            return Ok(None);
+        }
+
+        Ok(Some(response.get_blob_properties()?))
+    }
+
+    pub(crate) async fn update_sequence_number(
+        &self,
+        container_name: &str,
+        blob_name: &str,
+        action: SequenceNumberAction,
+    ) -> Result<u64, PageBlobError> {
+        let request = BlobRestRequest::new(Method::PUT, container_name, Some(blob_name))
+            .with_query("comp", "properties");
+
+        let request = match action {
+            SequenceNumberAction::Update(value) => request
+                .with_header("x-ms-sequence-number-action", "update")
+                .with_header("x-ms-blob-sequence-number", value.to_string()),
+            SequenceNumberAction::Max(value) => request
+                .with_header("x-ms-sequence-number-action", "max")
+                .with_header("x-ms-blob-sequence-number", value.to_string()),
+            SequenceNumberAction::Increment => {
+                request.with_header("x-ms-sequence-number-action", "increment")
+            }
+        };
+
+        let response = self.execute(request).await?;
+
+        response.parse_header("x-ms-blob-sequence-number")
+    }
+
+    // Fails with PreconditionFailed if the blob is changed after the ETag is read
+    pub(crate) async fn resize_if_match(
+        &self,
+        container_name: &str,
+        blob_name: &str,
+        pages_amount: usize,
+        etag: &str,
+    ) -> Result<(), PageBlobError> {
+        let request = BlobRestRequest::new(Method::PUT, container_name, Some(blob_name))
+            .with_query("comp", "properties")
+            .with_header(
+                "x-ms-blob-content-length",
+                (pages_amount * BLOB_PAGE_SIZE).to_string(),
+            )
+            .with_header("If-Match", etag);
+
+        self.execute(request).await?;
+
+        Ok(())
+    }
+
+    pub(crate) async fn save_pages(
+        &self,
+        container_name: &str,
+        blob_name: &str,
+        start_page_no: usize,
+        payload: Bytes,
+        condition: Option<SequenceNumberCondition>,
+    ) -> Result<(), PageBlobError> {
+        let start = start_page_no * BLOB_PAGE_SIZE;
+
+        let mut request = BlobRestRequest::new(Method::PUT, container_name, Some(blob_name))
+            .with_query("comp", "page")
+            .with_header("x-ms-page-write", "update")
+            .with_header(
+                "x-ms-range",
+                format!("bytes={}-{}", start, start + payload.len() - 1),
+            );
+
+        request = match condition {
+            Some(SequenceNumberCondition::LessOrEqual(value)) => {
+                request.with_header("x-ms-if-sequence-number-le", value.to_string())
+            }
+            Some(SequenceNumberCondition::LessThan(value)) => {
+                request.with_header("x-ms-if-sequence-number-lt", value.to_string())
+            }
+            Some(SequenceNumberCondition::Equal(value)) => {
+                request.with_header("x-ms-if-sequence-number-eq", value.to_string())
+            }
+            None => request,
+        };
+
+        self.execute(request.with_body(payload)).await?;
+
+        Ok(())
+    }
+
+    // Large blobs return the ranges by segments, NextMarker is the continuation token
+    pub(crate) async fn get_page_ranges_segment(
+        &self,
+        container_name: &str,
+        blob_name: &str,
+        marker: Option<&str>,
+    ) -> Result<(Vec<Range<usize>>, Option<String>), PageBlobError> {
+        let mut request = BlobRestRequest::new(Method::GET, container_name, Some(blob_name))
+            .with_query("comp", "pagelist");
+
+        if let Some(marker) = marker {
+            request = request.with_query("marker", marker);
+        }
+
+        let response = self.execute(request).await?;
+
+        parse_page_list_response(response.get_body_as_str()?)
+    }
+
+    // Metadata names come back lowercased, Azure compares them case insensitively anyway
+    pub(crate) async fn get_blob_metadata(
+        &self,
+        container_name: &str,
+        blob_name: &str,
+    ) -> Result<BTreeMap<String, String>, PageBlobError> {
+        let request = BlobRestRequest::new(Method::GET, container_name, Some(blob_name))
+            .with_query("comp", "metadata");
+
+        let response = self.execute(request).await?;
+
+        let result = response
+            .headers
+            .iter()
+            .filter_map(|(name, value)| {
+                name.strip_prefix(METADATA_HEADER_PREFIX)
+                    .map(|name| (name.to_string(), value.to_string()))
+            })
+            .collect();
+
+        Ok(result)
+    }
+
+    pub(crate) async fn set_blob_metadata(
+        &self,
+        container_name: &str,
+        blob_name: &str,
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<(), PageBlobError> {
+        let mut request = BlobRestRequest::new(Method::PUT, container_name, Some(blob_name))
+            .with_query("comp", "metadata");
+
+        for (name, value) in metadata {
+            request = request.with_header(&format!("{}{}", METADATA_HEADER_PREFIX, name), value);
+        }
+
+        self.execute(request).await?;
+
+        Ok(())
+    }
+
+    // Fails with ContainerNotFound, the properties themselves are not used
+    pub(crate) async fn get_container_properties(
+        &self,
+        container_name: &str,
+    ) -> Result<(), PageBlobError> {
+        let request = BlobRestRequest::new(Method::HEAD, container_name, None)
+            .with_query("restype", "container");
+
+        self.execute(request).await?;
+
+        Ok(())
+    }
+
+    // Sizes come with the listing and NextMarker is returned as the continuation token
+    pub(crate) async fn list_page_blobs_segment(
+        &self,
+        container_name: &str,
+        prefix: Option<&str>,
+        marker: Option<&str>,
+        max_results: usize,
+    ) -> Result<PageBlobsSegment, PageBlobError> {
+        let mut request = BlobRestRequest::new(Method::GET, container_name, None)
+            .with_query("restype", "container")
+            .with_query("comp", "list")
+            .with_query("maxresults", max_results);
+
+        if let Some(prefix) = prefix {
+            request = request.with_query("prefix", prefix);
+        }
+
+        if let Some(marker) = marker {
+            request = request.with_query("marker", marker);
+        }
+
+        let response = self.execute(request).await?;
+
+        parse_list_blobs_response(response.get_body_as_str()?)
+    }
+}
+
+pub(crate) struct BlobRestProperties {
+    pub blob_size: usize,
+    pub sequence_number: u64,
+    pub etag: String,
+}
+
+pub(crate) struct BlobRestRequest {
+    method: Method,
+    // Encoded path relative to the blob endpoint
+    path: String,
+    query: Vec<(&'static str, String)>,
+    headers: Vec<(String, String)>,
+    body: Bytes,
+}
+
+impl BlobRestRequest {
+    pub fn new(method: Method, container_name: &str, blob_name: Option<&str>) -> Self {
+        let path = match blob_name {
+            Some(blob_name) => format!(
+                "/{}/{}",
+                encode_uri_component(container_name, false),
+                encode_uri_component(blob_name, true)
+            ),
+            None => format!("/{}", encode_uri_component(container_name, false)),
+        };
+
+        Self {
+            method,
+            path,
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: Bytes::new(),
+        }
+    }
+
+    pub fn with_query(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.query.push((name, value.to_string()));
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: impl ToString) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_body(mut self, body: Bytes) -> Self {
+        self.body = body;
+        self
+    }
+}
+
+pub(crate) struct BlobRestResponse {
+    pub status: u16,
+    // Header names are lowercased
+    headers: Vec<(String, String)>,
+    pub body: Bytes,
+}
+
+impl BlobRestResponse {
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header_name, _)| header_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn parse_header<T: std::str::FromStr>(&self, name: &str) -> Result<T, PageBlobError> {
+        self.get_header(name)
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| {
+                AzureStorageError::UnknownError {
+                    msg: format!("Response has no valid {} header", name),
+                }
+                .into()
+            })
+    }
+
+    fn get_blob_properties(&self) -> Result<BlobRestProperties, PageBlobError> {
+        Ok(BlobRestProperties {
+            blob_size: self.parse_header("content-length")?,
+            sequence_number: self.parse_header("x-ms-blob-sequence-number")?,
+            etag: self.get_header("etag").unwrap_or_default().to_string(),
+        })
+    }
+
+    fn get_body_as_str(&self) -> Result<&str, PageBlobError> {
+        std::str::from_utf8(&self.body).map_err(|_| PageBlobError::Corrupted {
+            msg: "Blob REST response is not a valid UTF-8".to_string(),
+        })
+    }
+
+    fn into_error(self) -> PageBlobError {
+        let code = match self.get_header("x-ms-error-code") {
+            Some(code) => code.to_string(),
+            None => get_xml_value(&String::from_utf8_lossy(&self.body), "Code")
+                .unwrap_or_default()
+                .to_string(),
+        };
+
+        match (self.status, code.as_str()) {
+            (404, "ContainerNotFound") => AzureStorageError::ContainerNotFound.into(),
+            (404, "BlobNotFound") => AzureStorageError::BlobNotFound.into(),
+            (412, _) => PageBlobError::PreconditionFailed { msg: code },
+            (500..=599, _) => PageBlobError::ServerError {
+                status: self.status,
+                code,
+            },
+            _ => AzureStorageError::UnknownError {
+                msg: format!(
+                    "Blob REST request is failed with status {}. {}",
+                    self.status, code
+                ),
+            }
+            .into(),
+        }
+    }
+}
+
+fn get_string_to_sign(
+    account_name: &str,
+    endpoint_path: &str,
+    method: &Method,
+    path: &str,
+    query: &[(&'static str, String)],
+    headers: &[(String, String)],
+    content_length: usize,
+) -> String {
+    let get_standard_header = |name: &str| {
+        headers
+            .iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default()
+    };
+
+    let content_length = if content_length == 0 {
+        String::new()
+    } else {
+        content_length.to_string()
+    };
+
+    let mut result = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}\n\n{}\n{}\n{}\n{}\n{}\n",
+        method.as_str(),
+        get_standard_header("Content-Encoding"),
+        get_standard_header("Content-Language"),
+        content_length,
+        get_standard_header("Content-MD5"),
+        get_standard_header("Content-Type"),
+        get_standard_header("If-Modified-Since"),
+        get_standard_header("If-Match"),
+        get_standard_header("If-None-Match"),
+        get_standard_header("If-Unmodified-Since"),
+        get_standard_header("Range"),
+    );
+
+    let mut ms_headers: Vec<(String, &str)> = headers
+        .iter()
+        .map(|(name, value)| (name.to_lowercase(), value.trim()))
+        .filter(|(name, _)| name.starts_with("x-ms-"))
+        .collect();
+    ms_headers.sort();
+
+    for (name, value) in ms_headers {
+        result.push_str(&format!("{}:{}\n", name, value));
+    }
+
+    result.push_str(&format!("/{}{}{}", account_name, endpoint_path, path));
+
+    let mut query: Vec<&(&'static str, String)> = query.iter().collect();
+    query.sort();
+
+    for (name, value) in query {
+        result.push_str(&format!("\n{}:{}", name, value));
+    }
+
+    result
+}
+
+fn sign(account_key: &[u8], string_to_sign: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(account_key).expect("HMAC accepts keys of any length");
+    mac.update(string_to_sign.as_bytes());
+    BASE64.encode(mac.finalize().into_bytes())
+}
+
+// Path style endpoints of emulators include the account name, it is signed as a part of the path
+fn get_endpoint_path(blob_endpoint: &str) -> &str {
+    let without_scheme = match blob_endpoint.split_once("://") {
+        Some((_, without_scheme)) => without_scheme,
+        None => blob_endpoint,
+    };
+
+    match without_scheme.find('/') {
+        Some(index) => &without_scheme[index..],
+        None => "",
+    }
+}
+
+fn encode_uri_component(src: &str, keep_slashes: bool) -> String {
+    let mut result = String::with_capacity(src.len());
+
+    for b in src.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                result.push(b as char)
+            }
+            b'/' if keep_slashes => result.push('/'),
+            _ => result.push_str(&format!("%{:02X}", b)),
+        }
+    }
+
+    result
+}
+
+fn parse_list_blobs_response(xml: &str) -> Result<PageBlobsSegment, PageBlobError> {
+    let mut blobs = Vec::new();
+
+    for blob_xml in xml.split("<Blob>").skip(1) {
+        // Block and append blobs share the container with page blobs
+        if get_xml_value(blob_xml, "BlobType") != Some("PageBlob") {
+            continue;
+        }
+
+        let name = get_xml_value(blob_xml, "Name").ok_or_else(|| PageBlobError::Corrupted {
+            msg: "List Blobs response has a blob without a name".to_string(),
+        })?;
+
+        let blob_size = get_xml_value(blob_xml, "Content-Length")
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| PageBlobError::Corrupted {
+                msg: format!("List Blobs response has no Content-Length of {}", name),
+            })?;
+
+        blobs.push(PageBlobInfo {
+            name: decode_xml_entities(name),
+            blob_size,
+        });
+    }
+
+    let continuation_token = get_xml_value(xml, "NextMarker")
+        .filter(|marker| !marker.is_empty())
+        .map(decode_xml_entities);
+
+    Ok(PageBlobsSegment {
+        blobs,
+        continuation_token,
+    })
+}
+
+// Byte ranges with inclusive ends are converted to page ranges
+fn parse_page_list_response(
+    xml: &str,
+) -> Result<(Vec<Range<usize>>, Option<String>), PageBlobError> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+
+    for range_xml in xml.split("<PageRange>").skip(1) {
+        let start: usize = parse_xml_value(range_xml, "Start")?;
+        let end: usize = parse_xml_value(range_xml, "End")?;
+
+        let range = start / BLOB_PAGE_SIZE..(end + 1) / BLOB_PAGE_SIZE;
+
+        match ranges.last_mut() {
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => ranges.push(range),
+        }
+    }
+
+    let next_marker = get_xml_value(xml, "NextMarker")
+        .filter(|marker| !marker.is_empty())
+        .map(decode_xml_entities);
+
+    Ok((ranges, next_marker))
+}
+
+fn parse_xml_value<T: std::str::FromStr>(xml: &str, tag: &str) -> Result<T, PageBlobError> {
+    get_xml_value(xml, tag)
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| PageBlobError::Corrupted {
+            msg: format!("Blob REST response has no valid {}", tag),
+        })
+}
+
+fn decode_xml_entities(src: &str) -> String {
+    src.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+pub(crate) fn get_xml_value<'s>(xml: &'s str, tag: &str) -> Option<&'s str> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", tag))?;
+    Some(&xml[start..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list_blobs_response() {
+        let segment = parse_list_blobs_response(
+            "<EnumerationResults><Blobs><Blob><Name>a&amp;b</Name><Properties><Content-Length>1024</Content-Length><BlobType>PageBlob</BlobType></Properties></Blob><Blob><Name>block</Name><Properties><Content-Length>3</Content-Length><BlobType>BlockBlob</BlobType></Properties></Blob><Blob><Name>c</Name><Properties><Content-Length>0</Content-Length><BlobType>PageBlob</BlobType></Properties></Blob></Blobs><NextMarker>marker</NextMarker></EnumerationResults>",
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                PageBlobInfo {
+                    name: "a&b".to_string(),
+                    blob_size: 1024
+                },
+                PageBlobInfo {
+                    name: "c".to_string(),
+                    blob_size: 0
+                }
+            ],
+            segment.blobs
+        );
+        assert_eq!(Some("marker".to_string()), segment.continuation_token);
+
+        let segment = parse_list_blobs_response(
+            "<EnumerationResults><Blobs /><NextMarker /></EnumerationResults>",
+        )
+        .unwrap();
+
+        assert!(segment.blobs.is_empty());
+        assert_eq!(None, segment.continuation_token);
+    }
+
+    #[test]
+    fn test_parse_page_list_response() {
+        let (ranges, next_marker) = parse_page_list_response(
+            "<PageList><PageRange><Start>512</Start><End>1023</End></PageRange><PageRange><Start>1024</Start><End>1535</End></PageRange><PageRange><Start>4096</Start><End>4607</End></PageRange><NextMarker>marker</NextMarker></PageList>",
+        )
+        .unwrap();
+
+        assert_eq!(vec![1..3, 8..9], ranges);
+        assert_eq!(Some("marker".to_string()), next_marker);
+
+        let (ranges, next_marker) = parse_page_list_response("<PageList />").unwrap();
+
+        assert!(ranges.is_empty());
+        assert_eq!(None, next_marker);
+    }
+
+    #[test]
+    fn test_get_endpoint_path() {
+        assert_eq!("", get_endpoint_path("https://test.blob.core.windows.net"));
+        assert_eq!(
+            "/devstoreaccount1",
+            get_endpoint_path("http://127.0.0.1:10000/devstoreaccount1")
+        );
+    }
+
+    #[test]
+    fn test_string_to_sign() {
+        let request = BlobRestRequest::new(Method::PUT, "container", Some("tenant/1 a"))
+            .with_query("comp", "page")
+            .with_header("x-ms-range", "bytes=0-511")
+            .with_header("x-ms-page-write", "update")
+            .with_header("If-Match", "\"0x1\"");
+
+        let string_to_sign = get_string_to_sign(
+            "test",
+            "",
+            &request.method,
+            &request.path,
+            &request.query,
+            &request.headers,
+            512,
+        );
+
+        assert_eq!(
+            "PUT\n\n\n512\n\n\n\n\n\"0x1\"\n\n\n\nx-ms-page-write:update\nx-ms-range:bytes=0-511\n/test/container/tenant/1%20a\ncomp:page",
+            string_to_sign
+        );
+
+        assert_eq!(
+            "j7pJyOsqt+F73p1HQKcQ6uAyYNgGaWq2CV+PXbQg4LI=",
+            sign(b"key", &string_to_sign)
+        );
+    }
+
+    #[test]
+    fn test_rest_errors() {
+        let response = BlobRestResponse {
+            status: 412,
+            headers: vec![(
+                "x-ms-error-code".to_string(),
+                "SequenceNumberConditionNotMet".to_string(),
+            )],
+            body: Bytes::new(),
+        };
+
+        assert!(matches!(
+            response.into_error(),
+            PageBlobError::PreconditionFailed { .. }
+        ));
+
+        let response = BlobRestResponse {
+            status: 404,
+            headers: vec![],
+            body: Bytes::from_static(b"<Error><Code>BlobNotFound</Code></Error>"),
+        };
+
+        assert!(matches!(
+            response.into_error(),
+            PageBlobError::Azure(AzureStorageError::BlobNotFound)
+        ));
+
+        let response = BlobRestResponse {
+            status: 503,
+            headers: vec![("x-ms-error-code".to_string(), "ServerBusy".to_string())],
+            body: Bytes::new(),
+        };
+
+        assert!(matches!(
+            response.into_error(),
+            PageBlobError::ServerError { status: 503, .. }
+        ));
+    }
+}
@@ -0,0 +1,225 @@
+use std::{collections::BTreeMap, ops::Range};
+
+use crate::{MyPageBlob, PageBlobError};
+
+pub trait RelocationMap: Send {
+    fn get_relocation(&self, old_page_no: usize) -> Option<usize>;
+    fn set_relocation(&mut self, old_pages: Range<usize>, new_start_page_no: usize);
+}
+
+pub struct InMemoryRelocationMap {
+    // Start page of the moved range -> (end page of the moved range, new start page)
+    ranges: BTreeMap<usize, (usize, usize)>,
+}
+
+impl Default for InMemoryRelocationMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InMemoryRelocationMap {
+    pub fn new() -> Self {
+        Self {
+            ranges: BTreeMap::new(),
+        }
+    }
+}
+
+impl RelocationMap for InMemoryRelocationMap {
+    fn get_relocation(&self, old_page_no: usize) -> Option<usize> {
+        let (start, (end, new_start)) = self.ranges.range(..=old_page_no).next_back()?;
+
+        if old_page_no >= *end {
+            return None;
+        }
+
+        Some(new_start + old_page_no - start)
+    }
+
+    fn set_relocation(&mut self, old_pages: Range<usize>, new_start_page_no: usize) {
+        self.ranges
+            .insert(old_pages.start, (old_pages.end, new_start_page_no));
+    }
+}
+
+// Moves live ranges toward the start of the blob and shrinks it to the live pages amount.
+// Every chunk is recorded to the relocation map right after it is written and is never bigger than
+// the distance it is moved by, so source pages which are not relocated yet are never overwritten.
+// If compaction is interrupted, calling it again with the same live ranges and the relocation map
+// persisted by this compaction continues from the first chunk which is not relocated yet.
+pub async fn compact<TMyPageBlob: MyPageBlob + Send, TRelocationMap: RelocationMap>(
+    page_blob: &mut TMyPageBlob,
+    live_ranges: &[Range<usize>],
+    relocation_map: &mut TRelocationMap,
+) -> Result<usize, PageBlobError> {
+    let max_pages_to_write = page_blob.get_options().max_pages_to_write.max(1);
+
+    let mut live_ranges: Vec<Range<usize>> = live_ranges
+        .iter()
+        .filter(|range| !range.is_empty())
+        .cloned()
+        .collect();
+
+    live_ranges.sort_by_key(|range| range.start);
+
+    for pair in live_ranges.windows(2) {
+        if pair[0].end > pair[1].start {
+            return Err(PageBlobError::OverlappingRanges {
+                page_no: pair[1].start,
+            });
+        }
+    }
+
+    let mut new_start_page_no = 0;
+
+    for range in live_ranges {
+        let pages_amount = range.len();
+        let distance = range.start - new_start_page_no;
+
+        if distance == 0 {
+            relocation_map.set_relocation(range, new_start_page_no);
+            new_start_page_no += pages_amount;
+            continue;
+        }
+
+        let mut moved_pages = get_moved_pages_amount(relocation_map, &range, new_start_page_no);
+
+        while moved_pages < pages_amount {
+            let chunk_pages = (pages_amount - moved_pages)
+                .min(max_pages_to_write)
+                .min(distance);
+
+            let payload = page_blob
+                .get(range.start + moved_pages, chunk_pages)
+                .await?;
+
+            page_blob
+                .save_pages(new_start_page_no + moved_pages, max_pages_to_write, payload)
+                .await?;
+
+            relocation_map.set_relocation(
+                range.start + moved_pages..range.start + moved_pages + chunk_pages,
+                new_start_page_no + moved_pages,
+            );
+
+            moved_pages += chunk_pages;
+        }
+
+        new_start_page_no += pages_amount;
+    }
+
+    page_blob.resize(new_start_page_no).await?;
+
+    Ok(new_start_page_no)
+}
+
+// Chunks are relocated in order, so the relocated pages are always the prefix of the range
+fn get_moved_pages_amount<TRelocationMap: RelocationMap>(
+    relocation_map: &TRelocationMap,
+    range: &Range<usize>,
+    new_start_page_no: usize,
+) -> usize {
+    let mut from = 0;
+    let mut to = range.len();
+
+    while from < to {
+        let middle = (from + to) / 2;
+
+        if relocation_map.get_relocation(range.start + middle) == Some(new_start_page_no + middle) {
+            from = middle + 1;
+        } else {
+            to = middle;
+        }
+    }
+
+    from
+}
+
+#[cfg(test)]
+mod tests {
+    use my_azure_storage_sdk::page_blob::consts::BLOB_PAGE_SIZE;
+
+    use super::*;
+    use crate::MyPageBlobMock;
+
+    async fn create_blob() -> MyPageBlobMock {
+        let mut blob = MyPageBlobMock::new();
+        blob.options.max_pages_to_write = 2;
+        blob.create_container_if_not_exist().await.unwrap();
+        blob.create(10).await.unwrap();
+
+        for page_no in 0..10 {
+            blob.save_pages(page_no, 1, vec![page_no as u8; BLOB_PAGE_SIZE].into())
+                .await
+                .unwrap();
+        }
+
+        blob
+    }
+
+    #[tokio::test]
+    async fn test_compact() {
+        let mut blob = create_blob().await;
+        let mut relocation_map = InMemoryRelocationMap::new();
+
+        let pages_amount = compact(&mut blob, &[6..9, 1..2], &mut relocation_map)
+            .await
+            .unwrap();
+
+        assert_eq!(4, pages_amount);
+        assert_eq!(4, blob.get_available_pages_amount().await.unwrap());
+
+        let pages: Vec<u8> = blob
+            .get(0, 4)
+            .await
+            .unwrap()
+            .chunks(BLOB_PAGE_SIZE)
+            .map(|page| page[0])
+            .collect();
+
+        assert_eq!(vec![1, 6, 7, 8], pages);
+        assert_eq!(Some(0), relocation_map.get_relocation(1));
+        assert_eq!(Some(3), relocation_map.get_relocation(8));
+        assert_eq!(None, relocation_map.get_relocation(5));
+    }
+
+    #[tokio::test]
+    async fn test_compact_is_resumed() {
+        let mut blob = create_blob().await;
+        let mut relocation_map = InMemoryRelocationMap::new();
+
+        // Emulates the crash after the first chunk of 4..9 is moved to 0..2
+        let payload = blob.get(4, 2).await.unwrap();
+        blob.save_pages(0, 2, payload).await.unwrap();
+        relocation_map.set_relocation(4..6, 0);
+
+        compact(&mut blob, &[4..9], &mut relocation_map)
+            .await
+            .unwrap();
+
+        let pages: Vec<u8> = blob
+            .get(0, 5)
+            .await
+            .unwrap()
+            .chunks(BLOB_PAGE_SIZE)
+            .map(|page| page[0])
+            .collect();
+
+        assert_eq!(vec![4, 5, 6, 7, 8], pages);
+    }
+
+    #[tokio::test]
+    async fn test_compact_rejects_overlapping_ranges() {
+        let mut blob = create_blob().await;
+        let mut relocation_map = InMemoryRelocationMap::new();
+
+        let result = compact(&mut blob, &[4..7, 2..5], &mut relocation_map).await;
+
+        assert!(matches!(
+            result,
+            Err(PageBlobError::OverlappingRanges { page_no: 4 })
+        ));
+        assert_eq!(10, blob.get_available_pages_amount().await.unwrap());
+    }
+}
@@ -0,0 +1,567 @@
+use std::{collections::BTreeMap, future::Future};
+
+use bytes::Bytes;
+use futures::{StreamExt, TryStreamExt};
+use my_azure_storage_sdk::{page_blob::consts::BLOB_PAGE_SIZE, AzureStorageError};
+
+use crate::{FixedMultipleGrowth, MyPageBlob, PageBlobError};
+
+// Runs the same scenarios against any MyPageBlob implementation and panics on the first mismatch.
+// The factory must return a handle to a blob which does not exist yet. Its container is created by the suite.
+pub async fn run_conformance_suite<
+    TMyPageBlob: MyPageBlob + Send,
+    TFuture: Future<Output = TMyPageBlob>,
+    TFactory: Fn() -> TFuture,
+>(
+    factory: TFactory,
+) {
+    check_not_created_blob(create_blob(&factory).await).await;
+    check_create(create_blob(&factory).await).await;
+    check_create_overwrites(create_blob(&factory).await).await;
+    check_create_if_not_exists_keeps_data(create_blob(&factory).await).await;
+    check_save_and_get(create_blob(&factory).await).await;
+    check_save_out_of_range(create_blob(&factory).await).await;
+    check_save_in_chunks(create_blob(&factory).await).await;
+    check_empty_payload(create_blob(&factory).await).await;
+    check_save_page_ranges(create_blob(&factory).await).await;
+    check_get_page_ranges_batch(create_blob(&factory).await).await;
+    check_resize(create_blob(&factory).await).await;
+    check_auto_resize(create_blob(&factory).await).await;
+    check_download(create_blob(&factory).await).await;
+    check_download_stream(create_blob(&factory).await).await;
+    check_delete(create_blob(&factory).await).await;
+    check_metadata(create_blob(&factory).await).await;
+    check_written_page_ranges(create_blob(&factory).await).await;
+}
+
+async fn create_blob<
+    TMyPageBlob: MyPageBlob + Send,
+    TFuture: Future<Output = TMyPageBlob>,
+    TFactory: Fn() -> TFuture,
+>(
+    factory: &TFactory,
+) -> TMyPageBlob {
+    let mut page_blob = factory().await;
+    page_blob.create_container_if_not_exist().await.unwrap();
+    page_blob.delete_if_exists().await.unwrap();
+    page_blob
+}
+
+fn is_blob_not_found(result: &Result<impl Sized, PageBlobError>) -> bool {
+    matches!(
+        result,
+        Err(PageBlobError::Azure(AzureStorageError::BlobNotFound))
+    )
+}
+
+async fn check_not_created_blob(mut page_blob: impl MyPageBlob + Send) {
+    assert!(
+        is_blob_not_found(&page_blob.get_available_pages_amount().await),
+        "get_available_pages_amount of not created blob must fail with BlobNotFound"
+    );
+    assert!(
+        is_blob_not_found(&page_blob.get_blob_properties().await),
+        "get_blob_properties of not created blob must fail with BlobNotFound"
+    );
+    assert!(
+        is_blob_not_found(&page_blob.get(0, 1).await),
+        "get of not created blob must fail with BlobNotFound"
+    );
+    assert!(
+        is_blob_not_found(
+            &page_blob
+                .save_pages(0, 1, vec![1u8; BLOB_PAGE_SIZE].into())
+                .await
+        ),
+        "save_pages of not created blob must fail with BlobNotFound"
+    );
+}
+
+async fn check_create(mut page_blob: impl MyPageBlob + Send) {
+    page_blob.create(2).await.unwrap();
+
+    assert_eq!(2, page_blob.get_available_pages_amount().await.unwrap());
+    assert_eq!(
+        2 * BLOB_PAGE_SIZE,
+        page_blob.get_blob_properties().await.unwrap().blob_size
+    );
+    assert_eq!(
+        vec![0u8; 2 * BLOB_PAGE_SIZE],
+        page_blob.get(0, 2).await.unwrap(),
+        "created blob must be filled with zeros"
+    );
+}
+
+async fn check_create_overwrites(mut page_blob: impl MyPageBlob + Send) {
+    page_blob.create(2).await.unwrap();
+    page_blob
+        .save_pages(0, 2, vec![1u8; 2 * BLOB_PAGE_SIZE].into())
+        .await
+        .unwrap();
+
+    page_blob.create(1).await.unwrap();
+
+    assert_eq!(1, page_blob.get_available_pages_amount().await.unwrap());
+    assert_eq!(
+        vec![0u8; BLOB_PAGE_SIZE],
+        page_blob.get(0, 1).await.unwrap(),
+        "create must overwrite the existing blob"
+    );
+}
+
+async fn check_create_if_not_exists_keeps_data(mut page_blob: impl MyPageBlob + Send) {
+    page_blob.create_if_not_exists(2).await.unwrap();
+    page_blob
+        .save_pages(1, 1, vec![1u8; BLOB_PAGE_SIZE].into())
+        .await
+        .unwrap();
+
+    page_blob.create_if_not_exists(1).await.unwrap();
+
+    assert_eq!(2, page_blob.get_available_pages_amount().await.unwrap());
+    assert_eq!(
+        vec![1u8; BLOB_PAGE_SIZE],
+        page_blob.get(1, 1).await.unwrap(),
+        "create_if_not_exists must keep the existing blob"
+    );
+}
+
+async fn check_save_and_get(mut page_blob: impl MyPageBlob + Send) {
+    page_blob.create(3).await.unwrap();
+
+    let written = page_blob
+        .save_pages(1, 10, vec![5u8; 100].into())
+        .await
+        .unwrap();
+    assert_eq!(
+        BLOB_PAGE_SIZE, written,
+        "payload must be padded to the full page"
+    );
+
+    let page = page_blob.get(1, 1).await.unwrap();
+    assert_eq!(vec![5u8; 100], page[..100]);
+    assert_eq!(vec![0u8; BLOB_PAGE_SIZE - 100], page[100..]);
+
+    assert!(
+        page_blob.get(2, 2).await.is_err(),
+        "get beyond the blob size must fail"
+    );
+}
+
+async fn check_save_out_of_range(mut page_blob: impl MyPageBlob + Send) {
+    page_blob.create(2).await.unwrap();
+
+    let result = page_blob
+        .save_pages(1, 10, vec![1u8; 2 * BLOB_PAGE_SIZE].into())
+        .await;
+
+    assert!(
+        matches!(
+            result,
+            Err(PageBlobError::OutOfRange {
+                requested: 3,
+                available: 2
+            })
+        ),
+        "save_pages beyond the blob size must fail with OutOfRange. Result: {:?}",
+        result
+    );
+}
+
+async fn check_save_in_chunks(mut page_blob: impl MyPageBlob + Send) {
+    page_blob.create(5).await.unwrap();
+
+    let payload: Vec<u8> = (0..5 * BLOB_PAGE_SIZE)
+        .map(|i| (i / BLOB_PAGE_SIZE) as u8)
+        .collect();
+
+    page_blob
+        .save_pages(0, 2, payload.clone().into())
+        .await
+        .unwrap();
+
+    assert_eq!(payload, page_blob.get(0, 5).await.unwrap());
+}
+
+async fn check_empty_payload(mut page_blob: impl MyPageBlob + Send) {
+    page_blob.create(1).await.unwrap();
+
+    assert_eq!(
+        0,
+        page_blob.save_pages(0, 1, Bytes::new()).await.unwrap(),
+        "save_pages of empty payload must write nothing"
+    );
+    assert_eq!(
+        0,
+        page_blob.save_pages(5, 1, Bytes::new()).await.unwrap(),
+        "save_pages of empty payload must not check the range"
+    );
+    assert_eq!(
+        0,
+        page_blob
+            .auto_ressize_and_save_pages(5, 1, Bytes::new(), 4)
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        1,
+        page_blob.get_available_pages_amount().await.unwrap(),
+        "auto resize must not resize the blob for empty payload"
+    );
+
+    page_blob.resize(0).await.unwrap();
+    assert_eq!(0, page_blob.get_available_pages_amount().await.unwrap());
+    assert_eq!(Vec::<u8>::new(), page_blob.download().await.unwrap());
+}
+
+async fn check_save_page_ranges(mut page_blob: impl MyPageBlob + Send) {
+    page_blob.create(6).await.unwrap();
+
+    let results = page_blob
+        .save_page_ranges(vec![
+            (4, vec![4u8; BLOB_PAGE_SIZE].into()),
+            (0, vec![1u8; 10].into()),
+            (1, vec![2u8; BLOB_PAGE_SIZE].into()),
+            (5, Bytes::new()),
+        ])
+        .await
+        .unwrap();
+
+    let written: Vec<usize> = results.into_iter().map(|result| result.unwrap()).collect();
+    assert_eq!(
+        vec![BLOB_PAGE_SIZE, BLOB_PAGE_SIZE, BLOB_PAGE_SIZE, 0],
+        written
+    );
+
+    let pages = page_blob.get(0, 6).await.unwrap();
+    assert_eq!(vec![1u8; 10], pages[..10]);
+    assert_eq!(
+        vec![2u8; BLOB_PAGE_SIZE],
+        pages[BLOB_PAGE_SIZE..2 * BLOB_PAGE_SIZE]
+    );
+    assert_eq!(
+        vec![0u8; 2 * BLOB_PAGE_SIZE],
+        pages[2 * BLOB_PAGE_SIZE..4 * BLOB_PAGE_SIZE]
+    );
+    assert_eq!(
+        vec![4u8; BLOB_PAGE_SIZE],
+        pages[4 * BLOB_PAGE_SIZE..5 * BLOB_PAGE_SIZE]
+    );
+
+    let result = page_blob
+        .save_page_ranges(vec![
+            (2, vec![5u8; BLOB_PAGE_SIZE].into()),
+            (6, vec![5u8; BLOB_PAGE_SIZE].into()),
+        ])
+        .await;
+
+    assert!(
+        matches!(
+            result,
+            Err(PageBlobError::OutOfRange {
+                requested: 7,
+                available: 6
+            })
+        ),
+        "save_page_ranges beyond the blob size must fail with OutOfRange. Result: {:?}",
+        result
+    );
+    assert_eq!(
+        vec![0u8; BLOB_PAGE_SIZE],
+        page_blob.get(2, 1).await.unwrap(),
+        "no range must be written if any range is out of the blob size"
+    );
+
+    let result = page_blob
+        .save_page_ranges(vec![
+            (2, vec![5u8; 2 * BLOB_PAGE_SIZE].into()),
+            (3, vec![5u8; BLOB_PAGE_SIZE].into()),
+        ])
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(PageBlobError::OverlappingRanges { page_no: 3 })
+    ));
+}
+
+async fn check_get_page_ranges_batch(mut page_blob: impl MyPageBlob + Send) {
+    page_blob.create(40).await.unwrap();
+
+    let payload: Vec<u8> = (0..40 * BLOB_PAGE_SIZE)
+        .map(|i| (i / BLOB_PAGE_SIZE) as u8)
+        .collect();
+    page_blob
+        .save_pages(0, 40, payload.clone().into())
+        .await
+        .unwrap();
+
+    let ranges = [(30, 2), (0, 1), (2, 3), (3, 1), (39, 0), (39, 1)];
+    let result = page_blob.get_page_ranges_batch(&ranges).await.unwrap();
+
+    assert_eq!(ranges.len(), result.len());
+
+    for ((start_page_no, pages_amount), pages) in ranges.iter().zip(result) {
+        assert_eq!(
+            payload
+                [start_page_no * BLOB_PAGE_SIZE..(start_page_no + pages_amount) * BLOB_PAGE_SIZE],
+            pages[..],
+            "get_page_ranges_batch must return pages of the range starting at {}",
+            start_page_no
+        );
+    }
+
+    assert!(
+        page_blob
+            .get_page_ranges_batch(&[(0, 1), (39, 2)])
+            .await
+            .is_err(),
+        "get_page_ranges_batch beyond the blob size must fail"
+    );
+}
+
+async fn check_resize(mut page_blob: impl MyPageBlob + Send) {
+    page_blob.create(1).await.unwrap();
+    page_blob
+        .save_pages(0, 1, vec![1u8; BLOB_PAGE_SIZE].into())
+        .await
+        .unwrap();
+
+    page_blob.resize(3).await.unwrap();
+    assert_eq!(3, page_blob.get_available_pages_amount().await.unwrap());
+    assert_eq!(
+        vec![1u8; BLOB_PAGE_SIZE],
+        page_blob.get(0, 1).await.unwrap()
+    );
+    assert_eq!(
+        vec![0u8; 2 * BLOB_PAGE_SIZE],
+        page_blob.get(1, 2).await.unwrap()
+    );
+
+    page_blob.resize(1).await.unwrap();
+    assert_eq!(1, page_blob.get_available_pages_amount().await.unwrap());
+    assert!(page_blob.get(1, 1).await.is_err());
+
+    page_blob.resize(2).await.unwrap();
+    assert_eq!(
+        vec![0u8; BLOB_PAGE_SIZE],
+        page_blob.get(1, 1).await.unwrap(),
+        "pages cut by shrinking must be zeros after growing back"
+    );
+}
+
+async fn check_auto_resize(mut page_blob: impl MyPageBlob + Send) {
+    page_blob.create(2).await.unwrap();
+
+    page_blob
+        .auto_ressize_and_save_pages_with_growth(
+            5,
+            10,
+            vec![3u8; BLOB_PAGE_SIZE].into(),
+            &FixedMultipleGrowth::new(4),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(8, page_blob.get_available_pages_amount().await.unwrap());
+    assert_eq!(
+        vec![3u8; BLOB_PAGE_SIZE],
+        page_blob.get(5, 1).await.unwrap()
+    );
+
+    page_blob
+        .auto_ressize_and_save_pages_with_growth(
+            0,
+            10,
+            vec![4u8; BLOB_PAGE_SIZE].into(),
+            &FixedMultipleGrowth::new(4),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        8,
+        page_blob.get_available_pages_amount().await.unwrap(),
+        "auto resize must not resize the blob if the payload fits"
+    );
+}
+
+async fn check_download(mut page_blob: impl MyPageBlob + Send) {
+    page_blob.create(2).await.unwrap();
+    page_blob
+        .save_pages(1, 1, vec![1u8; BLOB_PAGE_SIZE].into())
+        .await
+        .unwrap();
+
+    let mut expected = vec![0u8; BLOB_PAGE_SIZE];
+    expected.extend(vec![1u8; BLOB_PAGE_SIZE]);
+
+    assert_eq!(expected, page_blob.download().await.unwrap());
+}
+
+async fn check_download_stream(mut page_blob: impl MyPageBlob + Send) {
+    assert!(
+        is_blob_not_found(&page_blob.download_stream(2).next().await.unwrap()),
+        "download_stream of not created blob must fail with BlobNotFound"
+    );
+
+    page_blob.create(5).await.unwrap();
+
+    let payload: Vec<u8> = (0..5 * BLOB_PAGE_SIZE)
+        .map(|i| (i / BLOB_PAGE_SIZE) as u8)
+        .collect();
+    page_blob
+        .save_pages(0, 5, payload.clone().into())
+        .await
+        .unwrap();
+
+    let chunks: Vec<Bytes> = page_blob.download_stream(2).try_collect().await.unwrap();
+
+    let chunk_sizes: Vec<usize> = chunks.iter().map(|chunk| chunk.len()).collect();
+    assert_eq!(
+        vec![2 * BLOB_PAGE_SIZE, 2 * BLOB_PAGE_SIZE, BLOB_PAGE_SIZE],
+        chunk_sizes
+    );
+    assert_eq!(payload, chunks.concat());
+
+    page_blob.resize(0).await.unwrap();
+    assert_eq!(0, page_blob.download_stream(2).count().await);
+}
+
+async fn check_delete(mut page_blob: impl MyPageBlob + Send) {
+    assert!(
+        is_blob_not_found(&page_blob.delete().await),
+        "delete of not created blob must fail with BlobNotFound"
+    );
+    page_blob.delete_if_exists().await.unwrap();
+
+    page_blob.create(1).await.unwrap();
+    page_blob
+        .save_pages(0, 1, vec![1u8; BLOB_PAGE_SIZE].into())
+        .await
+        .unwrap();
+    page_blob.delete().await.unwrap();
+
+    assert!(is_blob_not_found(
+        &page_blob.get_available_pages_amount().await
+    ));
+
+    page_blob.create_if_not_exists(1).await.unwrap();
+    assert_eq!(
+        vec![0u8; BLOB_PAGE_SIZE],
+        page_blob.get(0, 1).await.unwrap(),
+        "blob created after delete must not contain the deleted data"
+    );
+}
+
+async fn check_metadata(mut page_blob: impl MyPageBlob + Send) {
+    assert!(
+        is_blob_not_found(&page_blob.get_metadata().await),
+        "get_metadata of not created blob must fail with BlobNotFound"
+    );
+
+    page_blob.create(1).await.unwrap();
+    assert!(page_blob.get_metadata().await.unwrap().is_empty());
+
+    let metadata = BTreeMap::from([
+        ("logical_length".to_string(), "100".to_string()),
+        ("source".to_string(), "backup".to_string()),
+    ]);
+    page_blob.set_metadata(metadata.clone()).await.unwrap();
+    assert_eq!(metadata, page_blob.get_metadata().await.unwrap());
+
+    page_blob
+        .set_metadata(BTreeMap::from([(
+            "source".to_string(),
+            "import".to_string(),
+        )]))
+        .await
+        .unwrap();
+    assert_eq!(
+        BTreeMap::from([("source".to_string(), "import".to_string())]),
+        page_blob.get_metadata().await.unwrap(),
+        "set_metadata must replace all metadata"
+    );
+
+    page_blob.create(1).await.unwrap();
+    assert!(
+        page_blob.get_metadata().await.unwrap().is_empty(),
+        "create must drop the metadata of the overwritten blob"
+    );
+}
+
+async fn check_written_page_ranges(mut page_blob: impl MyPageBlob + Send) {
+    assert!(
+        is_blob_not_found(&page_blob.get_written_page_ranges().await),
+        "get_written_page_ranges of not created blob must fail with BlobNotFound"
+    );
+
+    page_blob.create(8).await.unwrap();
+    assert!(page_blob
+        .get_written_page_ranges()
+        .await
+        .unwrap()
+        .is_empty());
+
+    page_blob
+        .save_pages(1, 8, vec![1u8; 2 * BLOB_PAGE_SIZE].into())
+        .await
+        .unwrap();
+    page_blob
+        .save_pages(5, 8, vec![0u8; BLOB_PAGE_SIZE].into())
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![1..3, 5..6],
+        page_blob.get_written_page_ranges().await.unwrap(),
+        "pages written with zeros must be reported as written"
+    );
+
+    page_blob
+        .save_pages(3, 8, vec![3u8; BLOB_PAGE_SIZE].into())
+        .await
+        .unwrap();
+    page_blob
+        .save_pages(7, 8, vec![7u8; BLOB_PAGE_SIZE].into())
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![1..4, 5..6, 7..8],
+        page_blob.get_written_page_ranges().await.unwrap(),
+        "adjacent ranges must be merged"
+    );
+
+    page_blob.resize(6).await.unwrap();
+    assert_eq!(
+        vec![1..4, 5..6],
+        page_blob.get_written_page_ranges().await.unwrap(),
+        "resize must drop the ranges beyond the blob size"
+    );
+
+    page_blob.create(8).await.unwrap();
+    assert!(
+        page_blob
+            .get_written_page_ranges()
+            .await
+            .unwrap()
+            .is_empty(),
+        "create must drop the ranges of the overwritten blob"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MyPageBlobContainerMock, MyPageBlobMock, PageBlobContainer};
+
+    #[tokio::test]
+    async fn test_mock_conformance() {
+        run_conformance_suite(|| async { MyPageBlobMock::new() }).await;
+    }
+
+    #[tokio::test]
+    async fn test_mock_container_blob_conformance() {
+        let container = MyPageBlobContainerMock::new("container".to_string());
+        run_conformance_suite(|| async { container.open_blob("blob") }).await;
+    }
+}
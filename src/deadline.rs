@@ -0,0 +1,98 @@
+use std::{future::Future, time::Duration};
+
+use crate::{PageBlobError, WriteProgress};
+
+pub async fn execute_with_deadline<
+    TResult,
+    TFuture: Future<Output = Result<TResult, PageBlobError>>,
+>(
+    operation: &str,
+    timeout: Option<Duration>,
+    future: TFuture,
+) -> Result<TResult, PageBlobError> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return future.await,
+    };
+
+    match tokio::time::timeout(timeout, future).await {
+        Ok(result) => result,
+        Err(_) => Err(PageBlobError::Timeout {
+            operation: operation.to_string(),
+            timeout,
+            bytes_written: None,
+        }),
+    }
+}
+
+pub async fn execute_write_with_deadline<
+    TResult,
+    TFuture: Future<Output = Result<TResult, PageBlobError>>,
+>(
+    operation: &str,
+    timeout: Option<Duration>,
+    write_progress: &WriteProgress,
+    future: TFuture,
+) -> Result<TResult, PageBlobError> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return future.await,
+    };
+
+    match tokio::time::timeout(timeout, future).await {
+        Ok(result) => result,
+        Err(_) => Err(PageBlobError::Timeout {
+            operation: operation.to_string(),
+            timeout,
+            bytes_written: Some(write_progress.get_bytes_written()),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_deadline_is_exceeded() {
+        let result: Result<(), PageBlobError> =
+            execute_with_deadline("get", Some(Duration::from_millis(10)), async {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                Ok(())
+            })
+            .await;
+
+        assert!(result.unwrap_err().is_timeout());
+
+        let result = execute_with_deadline("get", None, async { Ok(5) }).await;
+        assert_eq!(5, result.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_write_deadline_reports_progress() {
+        let write_progress = WriteProgress::new();
+
+        let result: Result<(), PageBlobError> = execute_write_with_deadline(
+            "save_pages",
+            Some(Duration::from_millis(10)),
+            &write_progress,
+            async {
+                write_progress.add_bytes_written(512);
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                Ok(())
+            },
+        )
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(err.is_timeout());
+        assert!(matches!(
+            err,
+            PageBlobError::Timeout {
+                bytes_written: Some(512),
+                ..
+            }
+        ));
+        assert_eq!(512, write_progress.get_bytes_written());
+    }
+}
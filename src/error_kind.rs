@@ -0,0 +1,69 @@
+use my_azure_storage_sdk::AzureStorageError;
+
+use crate::PageBlobError;
+
+// Labels of metrics and traces, so the set is fixed and does not depend on the error payload
+pub fn get_error_kind(err: &PageBlobError) -> &'static str {
+    match err {
+        PageBlobError::OutOfRange { .. } => "OutOfRange",
+        PageBlobError::NotAligned { .. } => "NotAligned",
+        PageBlobError::BlobTooLarge { .. } => "BlobTooLarge",
+        PageBlobError::Corrupted { .. } => "Corrupted",
+        PageBlobError::Timeout { .. } => "Timeout",
+        PageBlobError::PreconditionFailed { .. } => "PreconditionFailed",
+        PageBlobError::SequenceNumberTooLarge { .. } => "SequenceNumberTooLarge",
+        PageBlobError::NotSupported { .. } => "NotSupported",
+        PageBlobError::OverlappingRanges { .. } => "OverlappingRanges",
+        PageBlobError::PartialWrite { .. } => "PartialWrite",
+        PageBlobError::ServerError { .. } => "ServerError",
+        PageBlobError::Io(_) => "Io",
+        // Kind of the sdk error rather than of the Azure wrapper
+        PageBlobError::Azure(azure_err) => get_azure_error_kind(azure_err),
+    }
+}
+
+fn get_azure_error_kind(err: &AzureStorageError) -> &'static str {
+    match err {
+        AzureStorageError::ContainerNotFound => "ContainerNotFound",
+        AzureStorageError::BlobNotFound => "BlobNotFound",
+        AzureStorageError::UnknownError { .. } => "UnknownError",
+        _ => "AzureStorageError",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kind() {
+        assert_eq!(
+            "BlobNotFound",
+            get_error_kind(&AzureStorageError::BlobNotFound.into())
+        );
+        assert_eq!(
+            "UnknownError",
+            get_error_kind(
+                &AzureStorageError::UnknownError {
+                    msg: "test".to_string()
+                }
+                .into()
+            )
+        );
+        assert_eq!(
+            "OutOfRange",
+            get_error_kind(&PageBlobError::OutOfRange {
+                requested: 2,
+                available: 1
+            })
+        );
+        assert_eq!(
+            "PartialWrite",
+            get_error_kind(&PageBlobError::PartialWrite {
+                pages_written: 1,
+                failed_pages: 1..2,
+                err: Box::new(AzureStorageError::BlobNotFound.into()),
+            })
+        );
+    }
+}
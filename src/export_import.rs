@@ -0,0 +1,593 @@
+use std::{
+    collections::BTreeMap,
+    io::SeekFrom,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use bytes::Bytes;
+use crc32fast::Hasher;
+use my_azure_storage_sdk::page_blob::consts::BLOB_PAGE_SIZE;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+};
+
+use crate::{
+    upload_from_reader::check_blob_does_not_exist, MyPageBlob, PageBlobError, MAX_PAGES_TO_WRITE,
+};
+
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub chunk_pages: usize,
+    pub write_manifest: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            chunk_pages: MAX_PAGES_TO_WRITE,
+            write_manifest: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    pub chunk_pages: usize,
+    // Otherwise the manifest is verified only if it exists
+    pub require_manifest: bool,
+    // Otherwise the import fails with PreconditionFailed if the blob exists
+    pub overwrite: bool,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            chunk_pages: MAX_PAGES_TO_WRITE,
+            require_manifest: false,
+            overwrite: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub container_name: String,
+    pub blob_name: String,
+    pub pages_amount: usize,
+    // Written pages of the blob. Pages out of these ranges are holes of the exported file
+    pub ranges: Vec<Range<usize>>,
+    // CRC-32 of the content of the ranges in their order
+    pub checksum: u32,
+    // Metadata of the blob, restored by the import
+    pub metadata: BTreeMap<String, String>,
+}
+
+// JSON content of the manifest file
+#[derive(Serialize, Deserialize)]
+struct ManifestFile {
+    version: u32,
+    #[serde(flatten)]
+    manifest: ExportManifest,
+}
+
+pub fn get_manifest_path(path: &Path) -> PathBuf {
+    let mut result = path.as_os_str().to_owned();
+    result.push(".manifest");
+    PathBuf::from(result)
+}
+
+// Writes only the written page ranges of the blob to the file, other pages stay holes of the file
+pub async fn export_to_file<TMyPageBlob: MyPageBlob + Send>(
+    page_blob: &mut TMyPageBlob,
+    path: &Path,
+    options: ExportOptions,
+) -> Result<ExportManifest, PageBlobError> {
+    let chunk_pages = options.chunk_pages.clamp(1, MAX_PAGES_TO_WRITE);
+
+    let pages_amount = page_blob.refresh_size().await?;
+    let metadata = page_blob.get_metadata().await?;
+
+    // The blob can be shrunk between the requests
+    let ranges: Vec<Range<usize>> = page_blob
+        .get_written_page_ranges()
+        .await?
+        .into_iter()
+        .filter(|range| range.start < pages_amount)
+        .map(|range| range.start..range.end.min(pages_amount))
+        .collect();
+
+    let mut file = File::create(path).await?;
+    let mut crc = Hasher::new();
+
+    for range in &ranges {
+        file.seek(SeekFrom::Start((range.start * BLOB_PAGE_SIZE) as u64))
+            .await?;
+
+        let mut page_no = range.start;
+
+        while page_no < range.end {
+            let pages_to_read = chunk_pages.min(range.end - page_no);
+            let chunk = page_blob.get(page_no, pages_to_read).await?;
+
+            crc.update(&chunk);
+            file.write_all(&chunk).await?;
+
+            page_no += pages_to_read;
+        }
+    }
+
+    file.set_len((pages_amount * BLOB_PAGE_SIZE) as u64).await?;
+    file.sync_all().await?;
+
+    let manifest = ExportManifest {
+        container_name: page_blob.get_container_name().to_string(),
+        blob_name: page_blob.get_blob_name().to_string(),
+        pages_amount,
+        ranges,
+        checksum: crc.finalize(),
+        metadata,
+    };
+
+    if options.write_manifest {
+        tokio::fs::write(get_manifest_path(path), serialize_manifest(&manifest)?).await?;
+    }
+
+    Ok(manifest)
+}
+
+// Verifies the file against the manifest before anything is written: the size, the checksum of the ranges
+// and that the file has no data out of the ranges. Then creates the blob with the size of the file, writes
+// the ranges and restores the metadata. Without a manifest the pages which are not zero are written.
+// The file is read twice, once to verify it and once to write it, so nothing is buffered in memory
+pub async fn import_from_file<TMyPageBlob: MyPageBlob + Send>(
+    page_blob: &mut TMyPageBlob,
+    path: &Path,
+    options: ImportOptions,
+) -> Result<ExportManifest, PageBlobError> {
+    let chunk_size = options.chunk_pages.clamp(1, MAX_PAGES_TO_WRITE) * BLOB_PAGE_SIZE;
+
+    let expected = match tokio::fs::read(get_manifest_path(path)).await {
+        Ok(content) => Some(deserialize_manifest(&content)?),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound && !options.require_manifest => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut file = File::open(path).await?;
+    let file_len = file.metadata().await?.len() as usize;
+
+    if file_len % BLOB_PAGE_SIZE != 0 {
+        return Err(PageBlobError::NotAligned { len: file_len });
+    }
+
+    let pages_amount = file_len / BLOB_PAGE_SIZE;
+
+    if let Some(expected) = &expected {
+        check_manifest_ranges(&expected.ranges, expected.pages_amount)?;
+    }
+
+    // Pages which are not zero
+    let mut file_ranges: Vec<Range<usize>> = Vec::new();
+    let mut crc = Hasher::new();
+    let mut page_no = 0;
+
+    while page_no < pages_amount {
+        let chunk = read_chunk(
+            &mut file,
+            chunk_size.min(file_len - page_no * BLOB_PAGE_SIZE),
+        )
+        .await?;
+
+        for page in chunk.chunks(BLOB_PAGE_SIZE) {
+            let is_zero = page.iter().all(|b| *b == 0);
+
+            if !is_zero {
+                push_page(&mut file_ranges, page_no);
+            }
+
+            let is_in_ranges = match &expected {
+                Some(expected) => is_covered(&expected.ranges, &(page_no..page_no + 1)),
+                None => !is_zero,
+            };
+
+            if is_in_ranges {
+                crc.update(page);
+            }
+
+            page_no += 1;
+        }
+    }
+
+    let checksum = crc.finalize();
+
+    if let Some(expected) = &expected {
+        if expected.pages_amount != pages_amount || expected.checksum != checksum {
+            return Err(PageBlobError::Corrupted {
+                msg: format!(
+                    "File {:?} does not match its manifest. Pages: {}, expected: {}. Checksum: {:08x}, expected: {:08x}",
+                    path, pages_amount, expected.pages_amount, checksum, expected.checksum
+                ),
+            });
+        }
+
+        if let Some(range) = file_ranges
+            .iter()
+            .find(|range| !is_covered(&expected.ranges, range))
+        {
+            return Err(PageBlobError::Corrupted {
+                msg: format!(
+                    "File {:?} has data out of the ranges of its manifest at pages {:?}",
+                    path, range
+                ),
+            });
+        }
+    }
+
+    if !options.overwrite {
+        check_blob_does_not_exist(page_blob).await?;
+    }
+
+    page_blob.create(pages_amount).await?;
+
+    let ranges = match &expected {
+        Some(expected) => expected.ranges.clone(),
+        None => file_ranges,
+    };
+
+    let max_pages_to_write = page_blob.get_options().max_pages_to_write;
+
+    for range in &ranges {
+        let mut pos = range.start * BLOB_PAGE_SIZE;
+        let end = range.end * BLOB_PAGE_SIZE;
+
+        file.seek(SeekFrom::Start(pos as u64)).await?;
+
+        while pos < end {
+            let chunk = read_chunk(&mut file, chunk_size.min(end - pos)).await?;
+            let chunk_len = chunk.len();
+
+            page_blob
+                .save_pages(pos / BLOB_PAGE_SIZE, max_pages_to_write, chunk)
+                .await?;
+
+            pos += chunk_len;
+        }
+    }
+
+    let result = match expected {
+        Some(expected) => {
+            if !expected.metadata.is_empty() {
+                page_blob.set_metadata(expected.metadata.clone()).await?;
+            }
+
+            expected
+        }
+        None => ExportManifest {
+            container_name: page_blob.get_container_name().to_string(),
+            blob_name: page_blob.get_blob_name().to_string(),
+            pages_amount,
+            ranges,
+            checksum,
+            metadata: BTreeMap::new(),
+        },
+    };
+
+    Ok(result)
+}
+
+async fn read_chunk(file: &mut File, len: usize) -> Result<Bytes, PageBlobError> {
+    let mut chunk = vec![0u8; len];
+    file.read_exact(&mut chunk).await?;
+    Ok(chunk.into())
+}
+
+fn push_page(ranges: &mut Vec<Range<usize>>, page_no: usize) {
+    match ranges.last_mut() {
+        Some(last) if last.end == page_no => last.end = page_no + 1,
+        _ => ranges.push(page_no..page_no + 1),
+    }
+}
+
+// Ranges are ordered and do not overlap
+fn is_covered(ranges: &[Range<usize>], inner: &Range<usize>) -> bool {
+    let index = ranges.partition_point(|range| range.end <= inner.start);
+
+    let mut covered_to = inner.start;
+
+    for range in &ranges[index..] {
+        if range.start > covered_to || covered_to >= inner.end {
+            break;
+        }
+
+        covered_to = range.end;
+    }
+
+    covered_to >= inner.end
+}
+
+fn check_manifest_ranges(
+    ranges: &[Range<usize>],
+    pages_amount: usize,
+) -> Result<(), PageBlobError> {
+    let mut prev_end = 0;
+
+    for range in ranges {
+        if range.start >= range.end || range.start < prev_end || range.end > pages_amount {
+            return Err(manifest_error(format!(
+                "Range {:?} is empty, not ordered or out of {} pages",
+                range, pages_amount
+            )));
+        }
+
+        prev_end = range.end;
+    }
+
+    Ok(())
+}
+
+fn serialize_manifest(manifest: &ExportManifest) -> Result<Vec<u8>, PageBlobError> {
+    let manifest_file = ManifestFile {
+        version: MANIFEST_VERSION,
+        manifest: manifest.clone(),
+    };
+
+    serde_json::to_vec_pretty(&manifest_file)
+        .map_err(|err| manifest_error(format!("Can not serialize. {}", err)))
+}
+
+fn deserialize_manifest(content: &[u8]) -> Result<ExportManifest, PageBlobError> {
+    let manifest_file: ManifestFile = serde_json::from_slice(content)
+        .map_err(|err| manifest_error(format!("Can not deserialize. {}", err)))?;
+
+    if manifest_file.version != MANIFEST_VERSION {
+        return Err(manifest_error(format!(
+            "Unsupported version {}",
+            manifest_file.version
+        )));
+    }
+
+    Ok(manifest_file.manifest)
+}
+
+fn manifest_error(msg: String) -> PageBlobError {
+    PageBlobError::Corrupted {
+        msg: format!("Invalid manifest. {}", msg),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MyPageBlobMock;
+
+    fn get_temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "my-azure-page-blob-{}-{}",
+            std::process::id(),
+            name
+        ))
+    }
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let manifest = ExportManifest {
+            container_name: "container".to_string(),
+            blob_name: "dir:blob".to_string(),
+            pages_amount: 10,
+            ranges: vec![0..2, 5..6],
+            checksum: 0xCBF43926,
+            metadata: BTreeMap::from([("reason: incident".to_string(), "a\nb\\c".to_string())]),
+        };
+
+        let content = serialize_manifest(&manifest).unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&content).unwrap();
+        assert_eq!(MANIFEST_VERSION, json["version"]);
+        assert_eq!(10, json["pages_amount"]);
+
+        assert_eq!(manifest, deserialize_manifest(&content).unwrap());
+    }
+
+    #[test]
+    fn test_manifest_unsupported_version() {
+        let content = br#"{"version":2,"container_name":"container","blob_name":"blob","pages_amount":0,"ranges":[],"checksum":0,"metadata":{}}"#;
+
+        assert!(matches!(
+            deserialize_manifest(content),
+            Err(PageBlobError::Corrupted { .. })
+        ));
+    }
+
+    #[test]
+    fn test_is_covered() {
+        let ranges = vec![1..3, 3..5, 7..8];
+
+        assert!(is_covered(&ranges, &(1..2)));
+        assert!(is_covered(&ranges, &(2..5)));
+        assert!(is_covered(&ranges, &(7..8)));
+        assert!(!is_covered(&ranges, &(0..1)));
+        assert!(!is_covered(&ranges, &(4..6)));
+        assert!(!is_covered(&ranges, &(8..9)));
+    }
+
+    async fn export_test_blob(path: &Path) -> (MyPageBlobMock, ExportManifest) {
+        let mut page_blob = MyPageBlobMock::new();
+        page_blob.create_container_if_not_exist().await.unwrap();
+        page_blob.create(10).await.unwrap();
+        page_blob
+            .save_pages(1, 10, vec![1u8; 2 * BLOB_PAGE_SIZE].into())
+            .await
+            .unwrap();
+        page_blob
+            .save_pages(5, 10, vec![0u8; BLOB_PAGE_SIZE].into())
+            .await
+            .unwrap();
+        page_blob
+            .save_pages(7, 10, vec![7u8; 10].into())
+            .await
+            .unwrap();
+        page_blob
+            .set_metadata(BTreeMap::from([(
+                "logical_length".to_string(),
+                "3594".to_string(),
+            )]))
+            .await
+            .unwrap();
+
+        let manifest = export_to_file(
+            &mut page_blob,
+            path,
+            ExportOptions {
+                chunk_pages: 4,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        (page_blob, manifest)
+    }
+
+    async fn create_existing_blob() -> MyPageBlobMock {
+        let mut page_blob = MyPageBlobMock::new();
+        page_blob.create_container_if_not_exist().await.unwrap();
+        page_blob.create(1).await.unwrap();
+        page_blob
+            .save_pages(0, 1, vec![9u8; BLOB_PAGE_SIZE].into())
+            .await
+            .unwrap();
+        page_blob
+    }
+
+    async fn remove_exported_files(path: &Path) {
+        tokio::fs::remove_file(path).await.unwrap();
+        tokio::fs::remove_file(get_manifest_path(path))
+            .await
+            .unwrap();
+    }
+
+    async fn corrupt_file(path: &Path, pos: usize, value: u8) {
+        let mut content = tokio::fs::read(path).await.unwrap();
+        content[pos] = value;
+        tokio::fs::write(path, &content).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import() {
+        let path = get_temp_path("round-trip");
+        let (mut page_blob, manifest) = export_test_blob(&path).await;
+
+        assert_eq!(10, manifest.pages_amount);
+        assert_eq!(vec![1..3, 5..6, 7..8], manifest.ranges);
+
+        let mut imported = MyPageBlobMock::new();
+        imported.create_container_if_not_exist().await.unwrap();
+
+        let imported_manifest = import_from_file(
+            &mut imported,
+            &path,
+            ImportOptions {
+                chunk_pages: 3,
+                require_manifest: true,
+                overwrite: false,
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(manifest, imported_manifest);
+        assert_eq!(
+            page_blob.download().await.unwrap(),
+            imported.download().await.unwrap()
+        );
+        assert_eq!(
+            manifest.ranges,
+            imported.get_written_page_ranges().await.unwrap()
+        );
+        assert_eq!(manifest.metadata, imported.get_metadata().await.unwrap());
+
+        remove_exported_files(&path).await;
+    }
+
+    #[tokio::test]
+    async fn test_import_does_not_overwrite_by_default() {
+        let path = get_temp_path("no-overwrite");
+        export_test_blob(&path).await;
+
+        let mut imported = create_existing_blob().await;
+
+        let result = import_from_file(&mut imported, &path, ImportOptions::default()).await;
+
+        assert!(matches!(
+            result,
+            Err(PageBlobError::PreconditionFailed { .. })
+        ));
+        assert_eq!(
+            vec![9u8; BLOB_PAGE_SIZE],
+            imported.download().await.unwrap()
+        );
+
+        remove_exported_files(&path).await;
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_data_out_of_ranges() {
+        let path = get_temp_path("out-of-ranges");
+        export_test_blob(&path).await;
+
+        // Page 0 is out of the ranges, so the checksum still matches
+        corrupt_file(&path, 0, 1).await;
+
+        let mut imported = create_existing_blob().await;
+
+        let result = import_from_file(
+            &mut imported,
+            &path,
+            ImportOptions {
+                overwrite: true,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        assert!(matches!(result, Err(PageBlobError::Corrupted { .. })));
+        assert_eq!(
+            vec![9u8; BLOB_PAGE_SIZE],
+            imported.download().await.unwrap()
+        );
+
+        remove_exported_files(&path).await;
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_checksum_mismatch() {
+        let path = get_temp_path("checksum");
+        export_test_blob(&path).await;
+
+        corrupt_file(&path, BLOB_PAGE_SIZE, 2).await;
+
+        let mut imported = create_existing_blob().await;
+
+        let result = import_from_file(
+            &mut imported,
+            &path,
+            ImportOptions {
+                overwrite: true,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        assert!(matches!(result, Err(PageBlobError::Corrupted { .. })));
+        assert_eq!(
+            vec![9u8; BLOB_PAGE_SIZE],
+            imported.download().await.unwrap()
+        );
+
+        remove_exported_files(&path).await;
+    }
+}
@@ -0,0 +1,112 @@
+pub trait GrowthStrategy: Send + Sync {
+    fn get_pages_amount_to_resize(
+        &self,
+        available_pages_amount: usize,
+        pages_amount_needs: usize,
+    ) -> usize;
+}
+
+// Rounds up to the multiple of the pages ratio
+pub struct FixedMultipleGrowth {
+    pub pages_ratio: usize,
+}
+
+impl FixedMultipleGrowth {
+    pub fn new(pages_ratio: usize) -> Self {
+        Self {
+            pages_ratio: pages_ratio.max(1),
+        }
+    }
+}
+
+impl GrowthStrategy for FixedMultipleGrowth {
+    fn get_pages_amount_to_resize(
+        &self,
+        _available_pages_amount: usize,
+        pages_amount_needs: usize,
+    ) -> usize {
+        return crate::sdk::get_ressize_to_pages_amount(pages_amount_needs, self.pages_ratio);
+    }
+}
+
+// Multiplies the blob size by the factor and rounds up to the multiple of the pages ratio
+pub struct GeometricGrowth {
+    pub factor: f64,
+    pub pages_ratio: usize,
+}
+
+impl GeometricGrowth {
+    pub fn new(factor: f64, pages_ratio: usize) -> Self {
+        Self {
+            factor,
+            pages_ratio: pages_ratio.max(1),
+        }
+    }
+}
+
+impl GrowthStrategy for GeometricGrowth {
+    fn get_pages_amount_to_resize(
+        &self,
+        available_pages_amount: usize,
+        pages_amount_needs: usize,
+    ) -> usize {
+        let grown = (available_pages_amount as f64 * self.factor).ceil() as usize;
+        return crate::sdk::get_ressize_to_pages_amount(
+            grown.max(pages_amount_needs),
+            self.pages_ratio,
+        );
+    }
+}
+
+// Grows geometrically until a single resize adds more than max_growth_pages, then grows linearly
+pub struct CappedGeometricGrowth {
+    pub factor: f64,
+    pub pages_ratio: usize,
+    pub max_growth_pages: usize,
+}
+
+impl CappedGeometricGrowth {
+    pub fn new(factor: f64, pages_ratio: usize, max_growth_pages: usize) -> Self {
+        Self {
+            factor,
+            pages_ratio: pages_ratio.max(1),
+            max_growth_pages,
+        }
+    }
+}
+
+impl GrowthStrategy for CappedGeometricGrowth {
+    fn get_pages_amount_to_resize(
+        &self,
+        available_pages_amount: usize,
+        pages_amount_needs: usize,
+    ) -> usize {
+        let grown = (available_pages_amount as f64 * self.factor).ceil() as usize;
+        let grown = grown.min(available_pages_amount + self.max_growth_pages);
+
+        return crate::sdk::get_ressize_to_pages_amount(
+            grown.max(pages_amount_needs),
+            self.pages_ratio,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_growth_strategies() {
+        let fixed = FixedMultipleGrowth::new(8);
+        assert_eq!(104, fixed.get_pages_amount_to_resize(100, 101));
+
+        let geometric = GeometricGrowth::new(1.5, 8);
+        assert_eq!(152, geometric.get_pages_amount_to_resize(100, 101));
+        assert_eq!(304, geometric.get_pages_amount_to_resize(100, 300));
+
+        let capped = CappedGeometricGrowth::new(2.0, 8, 24);
+        assert_eq!(16, capped.get_pages_amount_to_resize(8, 9));
+        assert_eq!(128, capped.get_pages_amount_to_resize(100, 101));
+        assert_eq!(304, capped.get_pages_amount_to_resize(100, 300));
+    }
+}
@@ -1,11 +1,76 @@
+mod blob_rest;
+mod compact;
+#[cfg(any(test, feature = "conformance"))]
+mod conformance;
+mod deadline;
+mod error_kind;
+mod export_import;
+mod growth_strategy;
+mod limits;
+mod list_page_blobs;
+mod metrics_page_blob;
 mod mock;
+mod mock_container;
+#[cfg(any(test, feature = "conformance"))]
+mod model_testing;
 mod my_azure_page_blob;
-
-mod my_azure_page_blob_with_telemetry;
+mod my_azure_page_blob_builder;
+mod my_azure_page_blob_container;
 mod my_page_blob;
+mod page_blob_container;
+#[cfg(feature = "emulator")]
+mod page_blob_emulator;
+mod page_blob_error;
+mod page_blob_options;
+mod page_ranges;
+mod pages_available_cache;
+#[cfg(feature = "prometheus")]
+mod prometheus_metrics;
 mod sdk;
+mod sequence_number;
+#[cfg(feature = "tracing")]
+mod tracing_page_blob;
+mod upload_from_reader;
+mod write_progress;
 
+pub use compact::{compact, InMemoryRelocationMap, RelocationMap};
+#[cfg(any(test, feature = "conformance"))]
+pub use conformance::run_conformance_suite;
+pub use export_import::{
+    export_to_file, get_manifest_path, import_from_file, ExportManifest, ExportOptions,
+    ImportOptions,
+};
+pub use growth_strategy::{
+    CappedGeometricGrowth, FixedMultipleGrowth, GeometricGrowth, GrowthStrategy,
+};
+pub use limits::{MAX_BLOB_SIZE, MAX_PAGES_TO_WRITE, MAX_PUT_PAGE_SIZE};
+pub use list_page_blobs::list_page_blobs;
+pub use metrics_page_blob::{MetricsPageBlob, PageBlobMetricsSink, PageBlobOperationMetric};
 pub use mock::MyPageBlobMock;
+pub use mock_container::{MockContainerBlob, MyPageBlobContainerMock};
+#[cfg(any(test, feature = "conformance"))]
+pub use model_testing::{check_against_model, operation_strategy, PageBlobOperation};
 pub use my_azure_page_blob::MyAzurePageBlob;
-pub use my_azure_page_blob_with_telemetry::MyAzurePageBlobWithTelemetry;
+pub use my_azure_page_blob_builder::MyAzurePageBlobBuilder;
+pub use my_azure_page_blob_container::MyAzurePageBlobContainer;
 pub use my_page_blob::MyPageBlob;
+pub use page_blob_container::{PageBlobContainer, PageBlobInfo, PageBlobsSegment};
+#[cfg(feature = "emulator")]
+pub use page_blob_emulator::{
+    get_emulator_connection_string, EmulatorError, PageBlobEmulator, EMULATOR_ACCOUNT_KEY,
+    EMULATOR_ACCOUNT_NAME,
+};
+pub use page_blob_error::PageBlobError;
+pub use page_blob_options::PageBlobOptions;
+pub use page_ranges::PageRangeResult;
+pub use pages_available_cache::PagesAvailableCachePolicy;
+#[cfg(feature = "prometheus")]
+pub use prometheus_metrics::PrometheusMetricsSink;
+pub use sequence_number::{SequenceNumberAction, SequenceNumberCondition, MAX_SEQUENCE_NUMBER};
+#[cfg(feature = "tracing")]
+pub use tracing_page_blob::TracingPageBlob;
+pub use upload_from_reader::{
+    get_logical_length, upload_from_reader, UploadOptions, UploadResult,
+    LOGICAL_LENGTH_METADATA_NAME,
+};
+pub use write_progress::WriteProgress;
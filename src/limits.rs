@@ -0,0 +1,79 @@
+use my_azure_storage_sdk::page_blob::consts::BLOB_PAGE_SIZE;
+
+use crate::PageBlobError;
+
+pub const MAX_BLOB_SIZE: u64 = 8 * 1024 * 1024 * 1024 * 1024;
+pub const MAX_PUT_PAGE_SIZE: usize = 4 * 1024 * 1024;
+pub const MAX_PAGES_TO_WRITE: usize = MAX_PUT_PAGE_SIZE / BLOB_PAGE_SIZE;
+
+pub fn check_blob_pages_amount(pages_amount: usize) -> Result<(), PageBlobError> {
+    let requested_size = (pages_amount as u64).saturating_mul(BLOB_PAGE_SIZE as u64);
+
+    if requested_size > MAX_BLOB_SIZE {
+        return Err(PageBlobError::BlobTooLarge {
+            requested_size,
+            max_size: MAX_BLOB_SIZE,
+        });
+    }
+
+    Ok(())
+}
+
+// Page numbers come from callers, so the end of a written range is checked instead of wrapping around
+pub fn get_end_page_no(start_page_no: usize, pages_amount: usize) -> Result<usize, PageBlobError> {
+    match start_page_no.checked_add(pages_amount) {
+        Some(end_page_no) => Ok(end_page_no),
+        None => Err(PageBlobError::BlobTooLarge {
+            requested_size: u64::MAX,
+            max_size: MAX_BLOB_SIZE,
+        }),
+    }
+}
+
+// Growth strategies may overshoot the max blob size when the blob is close to it
+pub fn clamp_blob_pages_amount(pages_amount: usize) -> usize {
+    let max_pages_amount =
+        usize::try_from(MAX_BLOB_SIZE / BLOB_PAGE_SIZE as u64).unwrap_or(usize::MAX);
+
+    pages_amount.min(max_pages_amount)
+}
+
+pub fn check_page_aligned(len: usize) -> Result<(), PageBlobError> {
+    if len % BLOB_PAGE_SIZE != 0 {
+        return Err(PageBlobError::NotAligned { len });
+    }
+
+    Ok(())
+}
+
+pub fn clamp_max_pages_to_write(max_pages_to_write: usize) -> usize {
+    max_pages_to_write.clamp(1, MAX_PAGES_TO_WRITE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        let max_pages_amount = (MAX_BLOB_SIZE / BLOB_PAGE_SIZE as u64) as usize;
+
+        assert!(check_blob_pages_amount(max_pages_amount).is_ok());
+        assert!(matches!(
+            check_blob_pages_amount(max_pages_amount + 1),
+            Err(PageBlobError::BlobTooLarge { .. })
+        ));
+
+        assert_eq!(10, clamp_blob_pages_amount(10));
+        assert_eq!(max_pages_amount, clamp_blob_pages_amount(usize::MAX));
+
+        assert!(check_page_aligned(1024).is_ok());
+        assert!(matches!(
+            check_page_aligned(1000),
+            Err(PageBlobError::NotAligned { len: 1000 })
+        ));
+
+        assert_eq!(1, clamp_max_pages_to_write(0));
+        assert_eq!(8192, clamp_max_pages_to_write(100_000));
+    }
+}
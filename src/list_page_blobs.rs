@@ -0,0 +1,68 @@
+use crate::{PageBlobContainer, PageBlobError, PageBlobInfo};
+use futures::{stream, Stream, TryStreamExt};
+
+pub fn list_page_blobs<'s, TPageBlobContainer: PageBlobContainer + Sync>(
+    container: &'s TPageBlobContainer,
+    prefix: Option<&'s str>,
+) -> impl Stream<Item = Result<PageBlobInfo, PageBlobError>> + 's {
+    // None - listing is finished. Some(None) - first segment is not requested yet
+    let first_segment: Option<Option<String>> = Some(None);
+
+    stream::unfold(first_segment, move |continuation_token| async move {
+        let continuation_token = continuation_token?;
+
+        match container
+            .list_page_blobs_segment(prefix, continuation_token)
+            .await
+        {
+            Ok(segment) => Some((Ok(segment.blobs), segment.continuation_token.map(Some))),
+            Err(err) => Some((Err(err), None)),
+        }
+    })
+    .map_ok(|blobs| stream::iter(blobs.into_iter().map(Ok)))
+    .try_flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MyPageBlob, MyPageBlobContainerMock};
+
+    #[tokio::test]
+    async fn test_list_page_blobs_by_segments() {
+        let container = MyPageBlobContainerMock::new_with_list_segment_size("test".to_string(), 2);
+        container.create_if_not_exists().await.unwrap();
+
+        for shard in 0..5 {
+            container
+                .open_blob(&format!("tenant-1/{}", shard))
+                .create(shard)
+                .await
+                .unwrap();
+        }
+
+        container.open_blob("tenant-2/0").create(1).await.unwrap();
+
+        let blobs: Vec<PageBlobInfo> = list_page_blobs(&container, Some("tenant-1/"))
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(5, blobs.len());
+
+        for (shard, blob) in blobs.iter().enumerate() {
+            assert_eq!(format!("tenant-1/{}", shard), blob.name);
+            assert_eq!(shard * 512, blob.blob_size);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_page_blobs_of_missing_container() {
+        let container = MyPageBlobContainerMock::new("test".to_string());
+
+        let result: Result<Vec<PageBlobInfo>, PageBlobError> =
+            list_page_blobs(&container, None).try_collect().await;
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,395 @@
+use std::{
+    collections::BTreeMap,
+    ops::Range,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use my_azure_storage_sdk::blob::BlobProperties;
+
+use crate::{
+    error_kind::get_error_kind, GrowthStrategy, MyPageBlob, PageBlobError, PageBlobOptions,
+    PageRangeResult, SequenceNumberAction, SequenceNumberCondition, WriteProgress,
+};
+
+pub struct PageBlobOperationMetric<'s> {
+    pub container: &'s str,
+    pub blob: &'s str,
+    pub operation: &'static str,
+    pub duration: Duration,
+    pub bytes_read: usize,
+    pub bytes_written: usize,
+    pub error_kind: Option<&'static str>,
+}
+
+pub trait PageBlobMetricsSink: Send + Sync {
+    fn record_operation(&self, metric: &PageBlobOperationMetric);
+}
+
+pub struct MetricsPageBlob<TMyPageBlob: MyPageBlob + Send + Sync> {
+    inner: TMyPageBlob,
+    sink: Arc<dyn PageBlobMetricsSink>,
+}
+
+impl<TMyPageBlob: MyPageBlob + Send + Sync> MetricsPageBlob<TMyPageBlob> {
+    pub fn new(inner: TMyPageBlob, sink: Arc<dyn PageBlobMetricsSink>) -> Self {
+        Self { inner, sink }
+    }
+
+    pub fn into_inner(self) -> TMyPageBlob {
+        self.inner
+    }
+
+    fn record<TResult>(
+        &self,
+        operation: &'static str,
+        started: Instant,
+        result: &Result<TResult, PageBlobError>,
+        bytes_read: usize,
+        bytes_written: usize,
+    ) {
+        let metric = PageBlobOperationMetric {
+            container: self.inner.get_container_name(),
+            blob: self.inner.get_blob_name(),
+            operation,
+            duration: started.elapsed(),
+            bytes_read,
+            bytes_written,
+            error_kind: result.as_ref().err().map(get_error_kind),
+        };
+
+        self.sink.record_operation(&metric);
+    }
+}
+
+#[async_trait]
+impl<TMyPageBlob: MyPageBlob + Send + Sync> MyPageBlob for MetricsPageBlob<TMyPageBlob> {
+    fn get_container_name(&self) -> &str {
+        return self.inner.get_container_name();
+    }
+
+    fn get_blob_name(&self) -> &str {
+        return self.inner.get_blob_name();
+    }
+
+    fn get_options(&self) -> &PageBlobOptions {
+        return self.inner.get_options();
+    }
+
+    async fn get_blob_properties(&mut self) -> Result<BlobProperties, PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.get_blob_properties().await;
+        self.record("get_blob_properties", started, &result, 0, 0);
+        result
+    }
+
+    async fn create(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.create(pages_amount).await;
+        self.record("create", started, &result, 0, 0);
+        result
+    }
+
+    async fn create_if_not_exists(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.create_if_not_exists(pages_amount).await;
+        self.record("create_if_not_exists", started, &result, 0, 0);
+        result
+    }
+
+    async fn get_available_pages_amount(&mut self) -> Result<usize, PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.get_available_pages_amount().await;
+        self.record("get_available_pages_amount", started, &result, 0, 0);
+        result
+    }
+
+    async fn refresh_size(&mut self) -> Result<usize, PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.refresh_size().await;
+        self.record("refresh_size", started, &result, 0, 0);
+        result
+    }
+
+    async fn create_container_if_not_exist(&mut self) -> Result<(), PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.create_container_if_not_exist().await;
+        self.record("create_container_if_not_exist", started, &result, 0, 0);
+        result
+    }
+
+    async fn resize(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.resize(pages_amount).await;
+        self.record("resize", started, &result, 0, 0);
+        result
+    }
+
+    async fn delete(&mut self) -> Result<(), PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.delete().await;
+        self.record("delete", started, &result, 0, 0);
+        result
+    }
+
+    async fn delete_if_exists(&mut self) -> Result<(), PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.delete_if_exists().await;
+        self.record("delete_if_exists", started, &result, 0, 0);
+        result
+    }
+
+    async fn get(
+        &mut self,
+        start_page_no: usize,
+        pages_amount: usize,
+    ) -> Result<Bytes, PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.get(start_page_no, pages_amount).await;
+        let bytes_read = result.as_ref().map(|payload| payload.len()).unwrap_or(0);
+        self.record("get", started, &result, bytes_read, 0);
+        result
+    }
+
+    async fn get_page_ranges_batch(
+        &mut self,
+        ranges: &[(usize, usize)],
+    ) -> Result<Vec<Bytes>, PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.get_page_ranges_batch(ranges).await;
+        let bytes_read = match &result {
+            Ok(payloads) => payloads.iter().map(|payload| payload.len()).sum(),
+            Err(_) => 0,
+        };
+        self.record("get_page_ranges_batch", started, &result, bytes_read, 0);
+        result
+    }
+
+    async fn save_pages(
+        &mut self,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+    ) -> Result<usize, PageBlobError> {
+        let started = Instant::now();
+        let result = self
+            .inner
+            .save_pages(start_page_no, max_pages_to_write, payload)
+            .await;
+        let bytes_written = *result.as_ref().unwrap_or(&0);
+        self.record("save_pages", started, &result, 0, bytes_written);
+        result
+    }
+
+    async fn save_pages_with_progress(
+        &mut self,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+        write_progress: WriteProgress,
+    ) -> Result<usize, PageBlobError> {
+        let started = Instant::now();
+        let result = self
+            .inner
+            .save_pages_with_progress(
+                start_page_no,
+                max_pages_to_write,
+                payload,
+                write_progress.clone(),
+            )
+            .await;
+        let bytes_written = write_progress.get_bytes_written();
+        self.record(
+            "save_pages_with_progress",
+            started,
+            &result,
+            0,
+            bytes_written,
+        );
+        result
+    }
+
+    async fn auto_ressize_and_save_pages_with_growth(
+        &mut self,
+        start_page_no: usize,
+        max_pages_to_write_single_round_trip: usize,
+        payload: Bytes,
+        growth_strategy: &dyn GrowthStrategy,
+    ) -> Result<usize, PageBlobError> {
+        let started = Instant::now();
+        let result = self
+            .inner
+            .auto_ressize_and_save_pages_with_growth(
+                start_page_no,
+                max_pages_to_write_single_round_trip,
+                payload,
+                growth_strategy,
+            )
+            .await;
+        let bytes_written = *result.as_ref().unwrap_or(&0);
+        self.record(
+            "auto_ressize_and_save_pages",
+            started,
+            &result,
+            0,
+            bytes_written,
+        );
+        result
+    }
+
+    async fn save_page_ranges(
+        &mut self,
+        ranges: Vec<(usize, Bytes)>,
+    ) -> Result<Vec<PageRangeResult>, PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.save_page_ranges(ranges).await;
+        let bytes_written = match &result {
+            Ok(results) => results
+                .iter()
+                .filter_map(|result| result.as_ref().ok())
+                .sum(),
+            Err(_) => 0,
+        };
+        self.record("save_page_ranges", started, &result, 0, bytes_written);
+        result
+    }
+
+    async fn download(&mut self) -> Result<Bytes, PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.download().await;
+        let bytes_read = result.as_ref().map(|payload| payload.len()).unwrap_or(0);
+        self.record("download", started, &result, bytes_read, 0);
+        result
+    }
+
+    async fn get_sequence_number(&mut self) -> Result<u64, PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.get_sequence_number().await;
+        self.record("get_sequence_number", started, &result, 0, 0);
+        result
+    }
+
+    async fn update_sequence_number(
+        &mut self,
+        action: SequenceNumberAction,
+    ) -> Result<u64, PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.update_sequence_number(action).await;
+        self.record("update_sequence_number", started, &result, 0, 0);
+        result
+    }
+
+    async fn resize_if_sequence_number(
+        &mut self,
+        condition: SequenceNumberCondition,
+        pages_amount: usize,
+    ) -> Result<(), PageBlobError> {
+        let started = Instant::now();
+        let result = self
+            .inner
+            .resize_if_sequence_number(condition, pages_amount)
+            .await;
+        self.record("resize_if_sequence_number", started, &result, 0, 0);
+        result
+    }
+
+    async fn save_pages_if_sequence_number(
+        &mut self,
+        condition: SequenceNumberCondition,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+    ) -> Result<usize, PageBlobError> {
+        let started = Instant::now();
+        let result = self
+            .inner
+            .save_pages_if_sequence_number(condition, start_page_no, max_pages_to_write, payload)
+            .await;
+        let bytes_written = *result.as_ref().unwrap_or(&0);
+        self.record(
+            "save_pages_if_sequence_number",
+            started,
+            &result,
+            0,
+            bytes_written,
+        );
+        result
+    }
+
+    async fn get_written_page_ranges(&mut self) -> Result<Vec<Range<usize>>, PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.get_written_page_ranges().await;
+        self.record("get_written_page_ranges", started, &result, 0, 0);
+        result
+    }
+
+    async fn get_metadata(&mut self) -> Result<BTreeMap<String, String>, PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.get_metadata().await;
+        self.record("get_metadata", started, &result, 0, 0);
+        result
+    }
+
+    async fn set_metadata(
+        &mut self,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<(), PageBlobError> {
+        let started = Instant::now();
+        let result = self.inner.set_metadata(metadata).await;
+        self.record("set_metadata", started, &result, 0, 0);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::MyPageBlobMock;
+
+    struct MetricsSinkMock {
+        operations: Mutex<Vec<(&'static str, usize, usize, Option<&'static str>)>>,
+    }
+
+    impl PageBlobMetricsSink for MetricsSinkMock {
+        fn record_operation(&self, metric: &PageBlobOperationMetric) {
+            self.operations.lock().unwrap().push((
+                metric.operation,
+                metric.bytes_read,
+                metric.bytes_written,
+                metric.error_kind,
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_operations() {
+        let sink = Arc::new(MetricsSinkMock {
+            operations: Mutex::new(Vec::new()),
+        });
+
+        let mut blob = MetricsPageBlob::new(MyPageBlobMock::new(), sink.clone());
+
+        assert!(blob.create(1).await.is_err());
+        blob.create_container_if_not_exist().await.unwrap();
+        blob.create(1).await.unwrap();
+        blob.save_pages(0, 10, vec![1u8; 10].into()).await.unwrap();
+        blob.get(0, 1).await.unwrap();
+
+        let operations = sink.operations.lock().unwrap();
+
+        assert_eq!(
+            vec![
+                ("create", 0, 0, Some("ContainerNotFound")),
+                ("create_container_if_not_exist", 0, 0, None),
+                ("create", 0, 0, None),
+                ("save_pages", 0, 512, None),
+                ("get", 512, 0, None),
+            ],
+            *operations
+        );
+    }
+}
@@ -1,13 +1,33 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
 use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
 use my_azure_storage_sdk::{
     blob::BlobProperties, page_blob::consts::BLOB_PAGE_SIZE, AzureStorageError,
 };
 
+use crate::{
+    limits::{check_blob_pages_amount, clamp_blob_pages_amount, get_end_page_no},
+    page_ranges::{
+        coalesce_page_ranges, get_page_range_results, get_pages_amount_needs, merge_page_ranges,
+        split_coalesced_payloads,
+    },
+    GrowthStrategy, PageBlobError, PageBlobOptions, PageRangeResult, SequenceNumberAction,
+    SequenceNumberCondition, WriteProgress,
+};
+
 use super::MyPageBlob;
 pub struct MyPageBlobMock {
     pub pages: Vec<[u8; BLOB_PAGE_SIZE]>,
     pub container_created: bool,
     pub blob_created: bool,
+    pub sequence_number: u64,
+    pub metadata: BTreeMap<String, String>,
+    pub written_pages: BTreeSet<usize>,
+    pub options: PageBlobOptions,
 }
 
 impl MyPageBlobMock {
@@ -16,6 +36,10 @@ impl MyPageBlobMock {
             pages: Vec::new(),
             container_created: false,
             blob_created: false,
+            sequence_number: 0,
+            metadata: BTreeMap::new(),
+            written_pages: BTreeSet::new(),
+            options: PageBlobOptions::default(),
         }
     }
 
@@ -24,22 +48,22 @@ impl MyPageBlobMock {
         self.pages.push(new_page);
     }
 
-    fn check_if_container_exists(&self) -> Result<(), AzureStorageError> {
+    fn check_if_container_exists(&self) -> Result<(), PageBlobError> {
         if self.container_created {
             return Ok(());
         }
 
-        Err(AzureStorageError::ContainerNotFound)
+        Err(AzureStorageError::ContainerNotFound.into())
     }
 
-    fn check_if_blob_exists(&self) -> Result<(), AzureStorageError> {
+    fn check_if_blob_exists(&self) -> Result<(), PageBlobError> {
         self.check_if_container_exists()?;
 
         if self.blob_created {
             return Ok(());
         }
 
-        Err(AzureStorageError::BlobNotFound)
+        Err(AzureStorageError::BlobNotFound.into())
     }
 }
 
@@ -53,9 +77,23 @@ impl MyPageBlob for MyPageBlobMock {
         return "Mock_BLOB";
     }
 
-    async fn create(&mut self, pages_amount: usize) -> Result<(), AzureStorageError> {
+    fn get_options(&self) -> &PageBlobOptions {
+        return &self.options;
+    }
+
+    async fn create(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        check_blob_pages_amount(pages_amount)?;
+
+        if self.options.auto_create_container {
+            self.container_created = true;
+        }
+
         self.check_if_container_exists()?;
         self.blob_created = true;
+        self.sequence_number = 0;
+        self.metadata.clear();
+        self.written_pages.clear();
+        self.pages.clear();
 
         while self.pages.len() < pages_amount {
             self.add_new_page();
@@ -63,10 +101,24 @@ impl MyPageBlob for MyPageBlobMock {
         Ok(())
     }
 
-    async fn create_if_not_exists(&mut self, pages_amount: usize) -> Result<(), AzureStorageError> {
+    async fn create_if_not_exists(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        check_blob_pages_amount(pages_amount)?;
+
+        if self.options.auto_create_container {
+            self.container_created = true;
+        }
+
         self.check_if_container_exists()?;
 
+        if self.blob_created {
+            return Ok(());
+        }
+
         self.blob_created = true;
+        self.sequence_number = 0;
+        self.metadata.clear();
+        self.written_pages.clear();
+        self.pages.clear();
 
         while self.pages.len() < pages_amount {
             self.add_new_page();
@@ -74,17 +126,23 @@ impl MyPageBlob for MyPageBlobMock {
         Ok(())
     }
 
-    async fn get_available_pages_amount(&mut self) -> Result<usize, AzureStorageError> {
+    async fn get_available_pages_amount(&mut self) -> Result<usize, PageBlobError> {
         self.check_if_blob_exists()?;
         Ok(self.pages.len())
     }
 
-    async fn create_container_if_not_exist(&mut self) -> Result<(), AzureStorageError> {
+    async fn refresh_size(&mut self) -> Result<usize, PageBlobError> {
+        return self.get_available_pages_amount().await;
+    }
+
+    async fn create_container_if_not_exist(&mut self) -> Result<(), PageBlobError> {
         self.container_created = true;
         Ok(())
     }
 
-    async fn resize(&mut self, pages_amount: usize) -> Result<(), AzureStorageError> {
+    async fn resize(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        check_blob_pages_amount(pages_amount)?;
+
         self.check_if_blob_exists()?;
 
         while self.pages.len() < pages_amount {
@@ -95,19 +153,28 @@ impl MyPageBlob for MyPageBlobMock {
             self.pages.remove(self.pages.len() - 1);
         }
 
+        self.written_pages.split_off(&pages_amount);
+
         Ok(())
     }
 
-    async fn delete(&mut self) -> Result<(), AzureStorageError> {
+    async fn delete(&mut self) -> Result<(), PageBlobError> {
         self.check_if_blob_exists()?;
 
         self.blob_created = false;
+        self.pages.clear();
+        self.sequence_number = 0;
+        self.metadata.clear();
+        self.written_pages.clear();
         return Ok(());
     }
 
-    async fn delete_if_exists(&mut self) -> Result<(), AzureStorageError> {
+    async fn delete_if_exists(&mut self) -> Result<(), PageBlobError> {
         self.blob_created = false;
         self.pages.clear();
+        self.sequence_number = 0;
+        self.metadata.clear();
+        self.written_pages.clear();
         return Ok(());
     }
 
@@ -115,44 +182,119 @@ impl MyPageBlob for MyPageBlobMock {
         &mut self,
         start_page_no: usize,
         pages_amount: usize,
-    ) -> Result<Vec<u8>, AzureStorageError> {
+    ) -> Result<Bytes, PageBlobError> {
         self.check_if_blob_exists()?;
 
-        let mut result = Vec::new();
+        let end_page_no = start_page_no.saturating_add(pages_amount);
+
+        if end_page_no > self.pages.len() {
+            return Err(PageBlobError::OutOfRange {
+                requested: end_page_no,
+                available: self.pages.len(),
+            });
+        }
+
+        let mut result = BytesMut::with_capacity(pages_amount * BLOB_PAGE_SIZE);
 
         let mut page_index = start_page_no;
 
-        while page_index < start_page_no + pages_amount {
-            result.extend(&self.pages[page_index]);
+        while page_index < end_page_no {
+            result.extend_from_slice(&self.pages[page_index]);
 
             page_index += 1;
         }
 
-        Ok(result)
+        Ok(result.freeze())
+    }
+
+    async fn get_page_ranges_batch(
+        &mut self,
+        ranges: &[(usize, usize)],
+    ) -> Result<Vec<Bytes>, PageBlobError> {
+        let coalesced_ranges = coalesce_page_ranges(ranges, self.options.max_read_gap_pages)?;
+
+        let mut payloads = Vec::with_capacity(coalesced_ranges.len());
+
+        for coalesced in &coalesced_ranges {
+            payloads.push(
+                self.get(coalesced.start_page_no, coalesced.pages_amount)
+                    .await?,
+            );
+        }
+
+        split_coalesced_payloads(ranges, &coalesced_ranges, payloads)
     }
 
     async fn save_pages(
+        &mut self,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+    ) -> Result<usize, PageBlobError> {
+        return self
+            .save_pages_with_progress(
+                start_page_no,
+                max_pages_to_write,
+                payload,
+                WriteProgress::new(),
+            )
+            .await;
+    }
+
+    async fn save_pages_with_progress(
         &mut self,
         start_page_no: usize,
         _max_pages_to_write: usize,
-        mut payload: Vec<u8>,
-    ) -> Result<usize, AzureStorageError> {
-        self.check_if_blob_exists()?;
-        super::sdk::ressize_payload_to_fullpage(&mut payload);
+        payload: Bytes,
+        write_progress: WriteProgress,
+    ) -> Result<usize, PageBlobError> {
+        if payload.is_empty() {
+            return Ok(0);
+        }
+
+        let payload = super::sdk::ressize_payload_to_fullpage(payload);
 
         let result = payload.len();
 
         let pages_amount = payload.len() / BLOB_PAGE_SIZE;
+
+        let end_page_no = match get_end_page_no(start_page_no, pages_amount) {
+            Ok(result) => result,
+            Err(err) => {
+                write_progress.set_failed_pages(start_page_no..usize::MAX);
+                return Err(err);
+            }
+        };
+
+        if let Err(err) = self.check_if_blob_exists() {
+            write_progress.set_failed_pages(start_page_no..end_page_no);
+            return Err(err);
+        }
+
+        if let Err(err) = check_blob_pages_amount(end_page_no) {
+            write_progress.set_failed_pages(start_page_no..end_page_no);
+            return Err(err);
+        }
+
+        if end_page_no > self.pages.len() {
+            write_progress.set_failed_pages(start_page_no..end_page_no);
+            return Err(PageBlobError::OutOfRange {
+                requested: end_page_no,
+                available: self.pages.len(),
+            });
+        }
         let mut page_index = start_page_no;
 
         let mut payload_index = 0;
 
-        while page_index < start_page_no + pages_amount {
+        while page_index < end_page_no {
             let slice = &payload[payload_index..payload_index + BLOB_PAGE_SIZE];
 
             let page = self.pages.get_mut(page_index).unwrap();
 
             page.copy_from_slice(slice);
+            self.written_pages.insert(page_index);
+            write_progress.add_bytes_written(BLOB_PAGE_SIZE);
 
             page_index += 1;
             payload_index += BLOB_PAGE_SIZE;
@@ -161,39 +303,310 @@ impl MyPageBlob for MyPageBlobMock {
         Ok(result)
     }
 
-    async fn auto_ressize_and_save_pages(
+    async fn auto_ressize_and_save_pages_with_growth(
         &mut self,
         start_page_no: usize,
         _max_pages_amount_to_write: usize,
-        mut payload: Vec<u8>,
-        resize_pages_ration: usize,
-    ) -> Result<usize, AzureStorageError> {
+        payload: Bytes,
+        growth_strategy: &dyn GrowthStrategy,
+    ) -> Result<usize, PageBlobError> {
+        if payload.is_empty() {
+            return Ok(0);
+        }
+
         self.check_if_blob_exists()?;
-        super::sdk::ressize_payload_to_fullpage(&mut payload);
+        let payload = super::sdk::ressize_payload_to_fullpage(payload);
         let pages_amount_after_append =
-            super::sdk::get_pages_amount_after_append(start_page_no, payload.len());
+            super::sdk::get_pages_amount_after_append(start_page_no, payload.len())?;
         if pages_amount_after_append > self.pages.len() {
-            let pages_amount_needes = super::sdk::get_ressize_to_pages_amount(
-                pages_amount_after_append,
-                resize_pages_ration,
+            let pages_amount_needes = clamp_blob_pages_amount(
+                growth_strategy
+                    .get_pages_amount_to_resize(self.pages.len(), pages_amount_after_append),
             );
 
             self.resize(pages_amount_needes).await?;
         }
 
-        return self.save_pages(start_page_no, 8000, payload).await;
+        return self
+            .save_pages(start_page_no, self.options.max_pages_to_write, payload)
+            .await;
     }
 
-    async fn download(&mut self) -> Result<Vec<u8>, AzureStorageError> {
+    async fn save_page_ranges(
+        &mut self,
+        ranges: Vec<(usize, Bytes)>,
+    ) -> Result<Vec<PageRangeResult>, PageBlobError> {
+        let ranges_amount = ranges.len();
+        let merged_ranges = merge_page_ranges(ranges)?;
+
+        if merged_ranges.is_empty() {
+            return Ok(get_page_range_results(
+                ranges_amount,
+                &merged_ranges,
+                vec![],
+            ));
+        }
+
+        self.check_if_blob_exists()?;
+
+        let pages_amount_needs = get_pages_amount_needs(&merged_ranges);
+
+        check_blob_pages_amount(pages_amount_needs)?;
+
+        if pages_amount_needs > self.pages.len() {
+            return Err(PageBlobError::OutOfRange {
+                requested: pages_amount_needs,
+                available: self.pages.len(),
+            });
+        }
+
+        let mut results = Vec::new();
+
+        for merged_range in &merged_ranges {
+            let result = self
+                .save_pages(
+                    merged_range.start_page_no,
+                    self.options.max_pages_to_write,
+                    merged_range.payload.clone(),
+                )
+                .await;
+
+            results.push(result.map(|_| ()));
+        }
+
+        Ok(get_page_range_results(
+            ranges_amount,
+            &merged_ranges,
+            results,
+        ))
+    }
+
+    async fn download(&mut self) -> Result<Bytes, PageBlobError> {
         self.check_if_blob_exists()?;
         return self.get(0, self.pages.len()).await;
     }
 
-    async fn get_blob_properties(&mut self) -> Result<BlobProperties, AzureStorageError> {
+    async fn get_blob_properties(&mut self) -> Result<BlobProperties, PageBlobError> {
+        self.check_if_blob_exists()?;
+
         let result = BlobProperties {
             blob_size: self.pages.len() * BLOB_PAGE_SIZE,
         };
 
         Ok(result)
     }
+
+    async fn get_sequence_number(&mut self) -> Result<u64, PageBlobError> {
+        self.check_if_blob_exists()?;
+        Ok(self.sequence_number)
+    }
+
+    async fn update_sequence_number(
+        &mut self,
+        action: SequenceNumberAction,
+    ) -> Result<u64, PageBlobError> {
+        self.check_if_blob_exists()?;
+        self.sequence_number = action.apply(self.sequence_number)?;
+        Ok(self.sequence_number)
+    }
+
+    async fn resize_if_sequence_number(
+        &mut self,
+        condition: SequenceNumberCondition,
+        pages_amount: usize,
+    ) -> Result<(), PageBlobError> {
+        self.check_if_blob_exists()?;
+        condition.check(self.sequence_number)?;
+        return self.resize(pages_amount).await;
+    }
+
+    async fn save_pages_if_sequence_number(
+        &mut self,
+        condition: SequenceNumberCondition,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+    ) -> Result<usize, PageBlobError> {
+        self.check_if_blob_exists()?;
+        condition.check(self.sequence_number)?;
+        return self
+            .save_pages(start_page_no, max_pages_to_write, payload)
+            .await;
+    }
+
+    async fn get_written_page_ranges(&mut self) -> Result<Vec<Range<usize>>, PageBlobError> {
+        self.check_if_blob_exists()?;
+
+        let mut result: Vec<Range<usize>> = Vec::new();
+
+        for page_no in self.written_pages.iter().copied() {
+            match result.last_mut() {
+                Some(last) if last.end == page_no => last.end = page_no + 1,
+                _ => result.push(page_no..page_no + 1),
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn get_metadata(&mut self) -> Result<BTreeMap<String, String>, PageBlobError> {
+        self.check_if_blob_exists()?;
+        Ok(self.metadata.clone())
+    }
+
+    async fn set_metadata(
+        &mut self,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<(), PageBlobError> {
+        self.check_if_blob_exists()?;
+        self.metadata = metadata;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_failed_write_reports_failed_pages() {
+        let mut blob = MyPageBlobMock::new();
+        blob.create_container_if_not_exist().await.unwrap();
+        blob.create(2).await.unwrap();
+
+        let write_progress = WriteProgress::new();
+
+        let result = blob
+            .save_pages_with_progress(1, 10, vec![1u8; 1024].into(), write_progress.clone())
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(PageBlobError::OutOfRange {
+                requested: 3,
+                available: 2
+            })
+        ));
+        assert_eq!(0, write_progress.get_pages_written());
+        assert_eq!(Some(1..3), write_progress.get_failed_pages());
+    }
+
+    #[tokio::test]
+    async fn test_save_with_options_defaults() {
+        let mut blob = MyPageBlobMock::new();
+        blob.options.auto_create_container = true;
+        blob.options.resize_pages_ratio = 4;
+
+        blob.create(0).await.unwrap();
+        blob.auto_ressize_and_save(1, vec![1u8; 512].into())
+            .await
+            .unwrap();
+
+        assert_eq!(4, blob.get_available_pages_amount().await.unwrap());
+
+        blob.save(3, vec![2u8; 512].into()).await.unwrap();
+        assert_eq!(vec![2u8; 512], blob.get(3, 1).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_auto_resize_with_growth_strategy() {
+        let mut blob = MyPageBlobMock::new();
+        blob.create_container_if_not_exist().await.unwrap();
+        blob.create(8).await.unwrap();
+
+        let growth_strategy = crate::GeometricGrowth::new(2.0, 1);
+
+        blob.auto_ressize_and_save_pages_with_growth(
+            8,
+            10,
+            vec![1u8; 512].into(),
+            &growth_strategy,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(16, blob.get_available_pages_amount().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_azure_limits_are_enforced() {
+        let mut blob = MyPageBlobMock::new();
+        blob.create_container_if_not_exist().await.unwrap();
+
+        let too_many_pages = (crate::MAX_BLOB_SIZE / BLOB_PAGE_SIZE as u64) as usize + 1;
+
+        assert!(matches!(
+            blob.create(too_many_pages).await,
+            Err(PageBlobError::BlobTooLarge { .. })
+        ));
+
+        blob.create(1).await.unwrap();
+
+        assert!(matches!(
+            blob.resize(too_many_pages).await,
+            Err(PageBlobError::BlobTooLarge { .. })
+        ));
+        assert!(matches!(
+            blob.save_pages(too_many_pages, 1, vec![1u8; 512].into())
+                .await,
+            Err(PageBlobError::BlobTooLarge { .. })
+        ));
+        assert!(matches!(
+            blob.save_pages(usize::MAX, 1, vec![1u8; 512].into()).await,
+            Err(PageBlobError::BlobTooLarge { .. })
+        ));
+        assert!(matches!(
+            blob.get(usize::MAX, 1).await,
+            Err(PageBlobError::OutOfRange { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_stale_writer_is_fenced() {
+        let mut blob = MyPageBlobMock::new();
+        blob.create_container_if_not_exist().await.unwrap();
+        blob.create(2).await.unwrap();
+
+        let fence = blob
+            .update_sequence_number(SequenceNumberAction::Increment)
+            .await
+            .unwrap();
+
+        assert_eq!(1, fence);
+
+        let new_fence = blob
+            .update_sequence_number(SequenceNumberAction::Increment)
+            .await
+            .unwrap();
+
+        let stale_write = blob
+            .save_pages_if_sequence_number(
+                SequenceNumberCondition::Equal(fence),
+                0,
+                10,
+                vec![1u8; 512].into(),
+            )
+            .await;
+
+        assert!(stale_write.is_err());
+        assert_eq!(vec![0u8; 512], blob.get(0, 1).await.unwrap());
+
+        blob.save_pages_if_sequence_number(
+            SequenceNumberCondition::Equal(new_fence),
+            0,
+            10,
+            vec![2u8; 512].into(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(vec![2u8; 512], blob.get(0, 1).await.unwrap());
+
+        let stale_resize = blob
+            .resize_if_sequence_number(SequenceNumberCondition::LessThan(new_fence), 4)
+            .await;
+
+        assert!(stale_resize.is_err());
+        assert_eq!(2, blob.get_available_pages_amount().await.unwrap());
+    }
 }
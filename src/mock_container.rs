@@ -0,0 +1,477 @@
+use std::{collections::BTreeMap, ops::Range, sync::Arc};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use my_azure_storage_sdk::{
+    blob::BlobProperties, page_blob::consts::BLOB_PAGE_SIZE, AzureStorageError,
+};
+use tokio::sync::Mutex;
+
+use crate::{
+    page_blob_container::get_segment_range, GrowthStrategy, MyPageBlob, MyPageBlobMock,
+    PageBlobContainer, PageBlobError, PageBlobInfo, PageBlobOptions, PageBlobsSegment,
+    PageRangeResult, SequenceNumberAction, SequenceNumberCondition, WriteProgress,
+};
+
+struct MockContainerData {
+    created: bool,
+    blobs: BTreeMap<String, MyPageBlobMock>,
+}
+
+impl MockContainerData {
+    fn get_blob_mut(&mut self, blob_name: &str, options: &PageBlobOptions) -> &mut MyPageBlobMock {
+        if !self.blobs.contains_key(blob_name) {
+            self.blobs
+                .insert(blob_name.to_string(), MyPageBlobMock::new());
+        }
+
+        let blob = self.blobs.get_mut(blob_name).unwrap();
+        blob.container_created = self.created;
+        blob.options = options.clone();
+        blob
+    }
+}
+
+// Sorted by name, as the blobs are kept in a BTreeMap
+fn get_created_blobs<'s>(
+    data: &'s MockContainerData,
+    prefix: Option<&str>,
+) -> Result<Vec<(&'s String, &'s MyPageBlobMock)>, PageBlobError> {
+    if !data.created {
+        return Err(AzureStorageError::ContainerNotFound.into());
+    }
+
+    let result = data
+        .blobs
+        .iter()
+        .filter(|(blob_name, blob)| {
+            blob.blob_created && prefix.is_none_or(|prefix| blob_name.starts_with(prefix))
+        })
+        .collect();
+
+    Ok(result)
+}
+
+pub struct MyPageBlobContainerMock {
+    container_name: String,
+    list_segment_size: usize,
+    options: PageBlobOptions,
+    data: Arc<Mutex<MockContainerData>>,
+}
+
+impl MyPageBlobContainerMock {
+    pub fn new(container_name: String) -> Self {
+        Self::new_with_list_segment_size(container_name, 100)
+    }
+
+    pub fn with_options(mut self, options: PageBlobOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn new_with_list_segment_size(container_name: String, list_segment_size: usize) -> Self {
+        Self {
+            container_name,
+            list_segment_size,
+            options: PageBlobOptions::default(),
+            data: Arc::new(Mutex::new(MockContainerData {
+                created: false,
+                blobs: BTreeMap::new(),
+            })),
+        }
+    }
+}
+
+#[async_trait]
+impl PageBlobContainer for MyPageBlobContainerMock {
+    type TPageBlob = MockContainerBlob;
+
+    fn get_container_name(&self) -> &str {
+        return self.container_name.as_str();
+    }
+
+    async fn exists(&self) -> Result<bool, PageBlobError> {
+        Ok(self.data.lock().await.created)
+    }
+
+    async fn create_if_not_exists(&self) -> Result<(), PageBlobError> {
+        self.data.lock().await.created = true;
+        Ok(())
+    }
+
+    async fn delete_container(&self) -> Result<(), PageBlobError> {
+        let mut data = self.data.lock().await;
+
+        if !data.created {
+            return Err(AzureStorageError::ContainerNotFound.into());
+        }
+
+        data.created = false;
+        data.blobs.clear();
+        Ok(())
+    }
+
+    async fn list_blobs(&self, prefix: Option<&str>) -> Result<Vec<String>, PageBlobError> {
+        let data = self.data.lock().await;
+
+        let blobs = get_created_blobs(&data, prefix)?
+            .into_iter()
+            .map(|(blob_name, _)| blob_name.to_string())
+            .collect();
+
+        Ok(blobs)
+    }
+
+    async fn list_page_blobs_segment(
+        &self,
+        prefix: Option<&str>,
+        continuation_token: Option<String>,
+    ) -> Result<PageBlobsSegment, PageBlobError> {
+        // Names and sizes are read under the same lock, so a blob deleted in between can not be missed
+        let data = self.data.lock().await;
+
+        let blobs = get_created_blobs(&data, prefix)?;
+
+        let blob_names: Vec<String> = blobs
+            .iter()
+            .map(|(blob_name, _)| blob_name.to_string())
+            .collect();
+
+        let (start, end, continuation_token) = get_segment_range(
+            &blob_names,
+            continuation_token.as_deref(),
+            self.list_segment_size,
+        );
+
+        let blobs = blobs[start..end]
+            .iter()
+            .map(|(blob_name, blob)| PageBlobInfo {
+                name: blob_name.to_string(),
+                blob_size: blob.pages.len() * BLOB_PAGE_SIZE,
+            })
+            .collect();
+
+        Ok(PageBlobsSegment {
+            blobs,
+            continuation_token,
+        })
+    }
+
+    fn open_blob(&self, blob_name: &str) -> MockContainerBlob {
+        MockContainerBlob {
+            container_name: self.container_name.clone(),
+            blob_name: blob_name.to_string(),
+            options: self.options.clone(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+pub struct MockContainerBlob {
+    container_name: String,
+    blob_name: String,
+    options: PageBlobOptions,
+    data: Arc<Mutex<MockContainerData>>,
+}
+
+#[async_trait]
+impl MyPageBlob for MockContainerBlob {
+    fn get_container_name(&self) -> &str {
+        return self.container_name.as_str();
+    }
+
+    fn get_blob_name(&self) -> &str {
+        return self.blob_name.as_str();
+    }
+
+    fn get_options(&self) -> &PageBlobOptions {
+        return &self.options;
+    }
+
+    async fn get_blob_properties(&mut self) -> Result<BlobProperties, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .get_blob_properties()
+            .await;
+    }
+
+    async fn create(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        let mut data = self.data.lock().await;
+
+        if self.options.auto_create_container {
+            data.created = true;
+        }
+
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .create(pages_amount)
+            .await;
+    }
+
+    async fn create_if_not_exists(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        let mut data = self.data.lock().await;
+
+        if self.options.auto_create_container {
+            data.created = true;
+        }
+
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .create_if_not_exists(pages_amount)
+            .await;
+    }
+
+    async fn get_available_pages_amount(&mut self) -> Result<usize, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .get_available_pages_amount()
+            .await;
+    }
+
+    async fn refresh_size(&mut self) -> Result<usize, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .refresh_size()
+            .await;
+    }
+
+    async fn create_container_if_not_exist(&mut self) -> Result<(), PageBlobError> {
+        self.data.lock().await.created = true;
+        Ok(())
+    }
+
+    async fn resize(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .resize(pages_amount)
+            .await;
+    }
+
+    async fn delete(&mut self) -> Result<(), PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .delete()
+            .await;
+    }
+
+    async fn delete_if_exists(&mut self) -> Result<(), PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .delete_if_exists()
+            .await;
+    }
+
+    async fn get(
+        &mut self,
+        start_page_no: usize,
+        pages_amount: usize,
+    ) -> Result<Bytes, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .get(start_page_no, pages_amount)
+            .await;
+    }
+
+    async fn get_page_ranges_batch(
+        &mut self,
+        ranges: &[(usize, usize)],
+    ) -> Result<Vec<Bytes>, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .get_page_ranges_batch(ranges)
+            .await;
+    }
+
+    async fn save_pages(
+        &mut self,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+    ) -> Result<usize, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .save_pages(start_page_no, max_pages_to_write, payload)
+            .await;
+    }
+
+    async fn save_pages_with_progress(
+        &mut self,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+        write_progress: WriteProgress,
+    ) -> Result<usize, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .save_pages_with_progress(start_page_no, max_pages_to_write, payload, write_progress)
+            .await;
+    }
+
+    async fn auto_ressize_and_save_pages_with_growth(
+        &mut self,
+        start_page_no: usize,
+        max_pages_to_write_single_round_trip: usize,
+        payload: Bytes,
+        growth_strategy: &dyn GrowthStrategy,
+    ) -> Result<usize, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .auto_ressize_and_save_pages_with_growth(
+                start_page_no,
+                max_pages_to_write_single_round_trip,
+                payload,
+                growth_strategy,
+            )
+            .await;
+    }
+
+    async fn save_page_ranges(
+        &mut self,
+        ranges: Vec<(usize, Bytes)>,
+    ) -> Result<Vec<PageRangeResult>, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .save_page_ranges(ranges)
+            .await;
+    }
+
+    async fn download(&mut self) -> Result<Bytes, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .download()
+            .await;
+    }
+
+    async fn get_sequence_number(&mut self) -> Result<u64, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .get_sequence_number()
+            .await;
+    }
+
+    async fn update_sequence_number(
+        &mut self,
+        action: SequenceNumberAction,
+    ) -> Result<u64, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .update_sequence_number(action)
+            .await;
+    }
+
+    async fn resize_if_sequence_number(
+        &mut self,
+        condition: SequenceNumberCondition,
+        pages_amount: usize,
+    ) -> Result<(), PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .resize_if_sequence_number(condition, pages_amount)
+            .await;
+    }
+
+    async fn save_pages_if_sequence_number(
+        &mut self,
+        condition: SequenceNumberCondition,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+    ) -> Result<usize, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .save_pages_if_sequence_number(condition, start_page_no, max_pages_to_write, payload)
+            .await;
+    }
+
+    async fn get_written_page_ranges(&mut self) -> Result<Vec<Range<usize>>, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .get_written_page_ranges()
+            .await;
+    }
+
+    async fn get_metadata(&mut self) -> Result<BTreeMap<String, String>, PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .get_metadata()
+            .await;
+    }
+
+    async fn set_metadata(
+        &mut self,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<(), PageBlobError> {
+        let mut data = self.data.lock().await;
+        return data
+            .get_blob_mut(&self.blob_name, &self.options)
+            .set_metadata(metadata)
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_and_delete_blobs() {
+        let container = MyPageBlobContainerMock::new("test".to_string());
+
+        assert!(!container.exists().await.unwrap());
+        container.create_if_not_exists().await.unwrap();
+        assert!(container.exists().await.unwrap());
+
+        container.open_blob("tenant-1/0").create(1).await.unwrap();
+        container.open_blob("tenant-1/1").create(1).await.unwrap();
+        container.open_blob("tenant-2/0").create(1).await.unwrap();
+
+        let mut not_created = container.open_blob("tenant-1/2");
+        assert!(not_created.get_available_pages_amount().await.is_err());
+
+        assert_eq!(
+            vec!["tenant-1/0".to_string(), "tenant-1/1".to_string()],
+            container.list_blobs(Some("tenant-1/")).await.unwrap()
+        );
+
+        assert_eq!(3, container.list_blobs(None).await.unwrap().len());
+
+        container.delete_container().await.unwrap();
+        assert!(!container.exists().await.unwrap());
+        assert!(container.open_blob("tenant-1/0").download().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_opened_blobs_share_data() {
+        let container = MyPageBlobContainerMock::new("test".to_string());
+        container.create_if_not_exists().await.unwrap();
+
+        let mut writer = container.open_blob("blob");
+        writer.create(1).await.unwrap();
+        writer
+            .save_pages(0, 10, vec![7u8; 512].into())
+            .await
+            .unwrap();
+
+        let mut reader = container.open_blob("blob");
+        assert_eq!(vec![7u8; 512], reader.get(0, 1).await.unwrap());
+    }
+}
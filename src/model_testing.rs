@@ -0,0 +1,364 @@
+use my_azure_storage_sdk::{page_blob::consts::BLOB_PAGE_SIZE, AzureStorageError};
+use proptest::prelude::*;
+
+use crate::{FixedMultipleGrowth, MyPageBlob, PageBlobError};
+
+#[derive(Debug, Clone)]
+pub enum PageBlobOperation {
+    Create(usize),
+    CreateIfNotExists(usize),
+    Resize(usize),
+    GetAvailablePagesAmount,
+    Get {
+        start_page_no: usize,
+        pages_amount: usize,
+    },
+    SavePages {
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Vec<u8>,
+    },
+    AutoResizeAndSavePages {
+        start_page_no: usize,
+        payload: Vec<u8>,
+        resize_pages_ratio: usize,
+    },
+    Download,
+    Delete,
+    DeleteIfExists,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ModelValue {
+    Done,
+    PagesAmount(usize),
+    Payload(Vec<u8>),
+}
+
+#[derive(Debug)]
+enum ModelError {
+    BlobNotFound,
+    OutOfRange,
+    // Implementations are free to choose the error, e.g. reading beyond the blob size
+    Any,
+}
+
+// Reference model of a single page blob inside an existing container
+struct PageBlobModel {
+    blob: Option<Vec<u8>>,
+}
+
+impl PageBlobModel {
+    pub fn new() -> Self {
+        Self { blob: None }
+    }
+
+    fn apply(&mut self, operation: &PageBlobOperation) -> Result<ModelValue, ModelError> {
+        match operation {
+            // Empty writes are no-ops which do not even check the blob exists
+            PageBlobOperation::SavePages { payload, .. }
+            | PageBlobOperation::AutoResizeAndSavePages { payload, .. }
+                if payload.is_empty() =>
+            {
+                Ok(ModelValue::PagesAmount(0))
+            }
+            PageBlobOperation::Create(pages_amount) => {
+                self.blob = Some(vec![0u8; pages_amount * BLOB_PAGE_SIZE]);
+                Ok(ModelValue::Done)
+            }
+            PageBlobOperation::CreateIfNotExists(pages_amount) => {
+                if self.blob.is_none() {
+                    self.blob = Some(vec![0u8; pages_amount * BLOB_PAGE_SIZE]);
+                }
+                Ok(ModelValue::Done)
+            }
+            PageBlobOperation::Resize(pages_amount) => {
+                self.get_blob_mut()?
+                    .resize(pages_amount * BLOB_PAGE_SIZE, 0);
+                Ok(ModelValue::Done)
+            }
+            PageBlobOperation::GetAvailablePagesAmount => {
+                let blob = self.get_blob_mut()?;
+                Ok(ModelValue::PagesAmount(blob.len() / BLOB_PAGE_SIZE))
+            }
+            PageBlobOperation::Get {
+                start_page_no,
+                pages_amount,
+            } => {
+                let blob = self.get_blob_mut()?;
+                let start = start_page_no * BLOB_PAGE_SIZE;
+                let end = start + pages_amount * BLOB_PAGE_SIZE;
+
+                if end > blob.len() {
+                    return Err(ModelError::Any);
+                }
+
+                Ok(ModelValue::Payload(blob[start..end].to_vec()))
+            }
+            PageBlobOperation::SavePages {
+                start_page_no,
+                payload,
+                ..
+            } => {
+                let blob = self.get_blob_mut()?;
+                let payload = pad_to_full_pages(payload);
+                let start = start_page_no * BLOB_PAGE_SIZE;
+
+                if start + payload.len() > blob.len() {
+                    return Err(ModelError::OutOfRange);
+                }
+
+                blob[start..start + payload.len()].copy_from_slice(&payload);
+                Ok(ModelValue::PagesAmount(payload.len() / BLOB_PAGE_SIZE))
+            }
+            PageBlobOperation::AutoResizeAndSavePages {
+                start_page_no,
+                payload,
+                resize_pages_ratio,
+            } => {
+                let blob = self.get_blob_mut()?;
+                let payload = pad_to_full_pages(payload);
+                let start = start_page_no * BLOB_PAGE_SIZE;
+                let pages_amount_needs = start_page_no + payload.len() / BLOB_PAGE_SIZE;
+
+                if pages_amount_needs * BLOB_PAGE_SIZE > blob.len() {
+                    let ratio = (*resize_pages_ratio).max(1);
+                    let pages_amount = pages_amount_needs.div_ceil(ratio) * ratio;
+                    blob.resize(pages_amount * BLOB_PAGE_SIZE, 0);
+                }
+
+                blob[start..start + payload.len()].copy_from_slice(&payload);
+                Ok(ModelValue::PagesAmount(payload.len() / BLOB_PAGE_SIZE))
+            }
+            PageBlobOperation::Download => {
+                let blob = self.get_blob_mut()?;
+                Ok(ModelValue::Payload(blob.clone()))
+            }
+            PageBlobOperation::Delete => {
+                self.get_blob_mut()?;
+                self.blob = None;
+                Ok(ModelValue::Done)
+            }
+            PageBlobOperation::DeleteIfExists => {
+                self.blob = None;
+                Ok(ModelValue::Done)
+            }
+        }
+    }
+
+    fn get_blob_mut(&mut self) -> Result<&mut Vec<u8>, ModelError> {
+        self.blob.as_mut().ok_or(ModelError::BlobNotFound)
+    }
+}
+
+// Applies operations to the page blob and to the reference model and returns the description
+// of the first operation which result differs. The container of the page blob must exist.
+pub async fn check_against_model<TMyPageBlob: MyPageBlob + Send>(
+    page_blob: &mut TMyPageBlob,
+    operations: &[PageBlobOperation],
+) -> Result<(), String> {
+    let mut model = PageBlobModel::new();
+
+    for (index, operation) in operations.iter().enumerate() {
+        let expected = model.apply(operation);
+        let actual = execute_operation(page_blob, operation).await;
+
+        let matches = match (&expected, &actual) {
+            (Ok(expected), Ok(actual)) => expected == actual,
+            (Err(ModelError::BlobNotFound), Err(err)) => {
+                matches!(err, PageBlobError::Azure(AzureStorageError::BlobNotFound))
+            }
+            (Err(ModelError::OutOfRange), Err(err)) => {
+                matches!(err, PageBlobError::OutOfRange { .. })
+            }
+            (Err(ModelError::Any), Err(_)) => true,
+            _ => false,
+        };
+
+        if !matches {
+            return Err(format!(
+                "Operation #{} {:?} mismatch. Expected: {:?}. Actual: {:?}",
+                index,
+                operation,
+                expected,
+                actual.map(|value| match value {
+                    ModelValue::Payload(payload) => format!("Payload of {} bytes", payload.len()),
+                    value => format!("{:?}", value),
+                })
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_operation<TMyPageBlob: MyPageBlob + Send>(
+    page_blob: &mut TMyPageBlob,
+    operation: &PageBlobOperation,
+) -> Result<ModelValue, PageBlobError> {
+    match operation {
+        PageBlobOperation::Create(pages_amount) => {
+            page_blob.create(*pages_amount).await?;
+            Ok(ModelValue::Done)
+        }
+        PageBlobOperation::CreateIfNotExists(pages_amount) => {
+            page_blob.create_if_not_exists(*pages_amount).await?;
+            Ok(ModelValue::Done)
+        }
+        PageBlobOperation::Resize(pages_amount) => {
+            page_blob.resize(*pages_amount).await?;
+            Ok(ModelValue::Done)
+        }
+        PageBlobOperation::GetAvailablePagesAmount => Ok(ModelValue::PagesAmount(
+            page_blob.get_available_pages_amount().await?,
+        )),
+        PageBlobOperation::Get {
+            start_page_no,
+            pages_amount,
+        } => Ok(ModelValue::Payload(
+            page_blob.get(*start_page_no, *pages_amount).await?.to_vec(),
+        )),
+        PageBlobOperation::SavePages {
+            start_page_no,
+            max_pages_to_write,
+            payload,
+        } => {
+            let written = page_blob
+                .save_pages(*start_page_no, *max_pages_to_write, payload.clone().into())
+                .await?;
+            Ok(ModelValue::PagesAmount(written / BLOB_PAGE_SIZE))
+        }
+        PageBlobOperation::AutoResizeAndSavePages {
+            start_page_no,
+            payload,
+            resize_pages_ratio,
+        } => {
+            let written = page_blob
+                .auto_ressize_and_save_pages_with_growth(
+                    *start_page_no,
+                    page_blob.get_options().max_pages_to_write,
+                    payload.clone().into(),
+                    &FixedMultipleGrowth::new(*resize_pages_ratio),
+                )
+                .await?;
+            Ok(ModelValue::PagesAmount(written / BLOB_PAGE_SIZE))
+        }
+        PageBlobOperation::Download => {
+            Ok(ModelValue::Payload(page_blob.download().await?.to_vec()))
+        }
+        PageBlobOperation::Delete => {
+            page_blob.delete().await?;
+            Ok(ModelValue::Done)
+        }
+        PageBlobOperation::DeleteIfExists => {
+            page_blob.delete_if_exists().await?;
+            Ok(ModelValue::Done)
+        }
+    }
+}
+
+// Generated operations keep blobs small, so the sequences hit the blob edges often
+const MAX_PAGES: usize = 8;
+
+pub fn operation_strategy() -> impl Strategy<Value = PageBlobOperation> {
+    let payload = prop::collection::vec(any::<u8>(), 0..3 * BLOB_PAGE_SIZE);
+
+    prop_oneof![
+        (0..MAX_PAGES).prop_map(PageBlobOperation::Create),
+        (0..MAX_PAGES).prop_map(PageBlobOperation::CreateIfNotExists),
+        (0..MAX_PAGES).prop_map(PageBlobOperation::Resize),
+        Just(PageBlobOperation::GetAvailablePagesAmount),
+        (0..MAX_PAGES, 1..4usize).prop_map(|(start_page_no, pages_amount)| {
+            PageBlobOperation::Get {
+                start_page_no,
+                pages_amount,
+            }
+        }),
+        (0..MAX_PAGES, 1..4usize, payload.clone()).prop_map(
+            |(start_page_no, max_pages_to_write, payload)| PageBlobOperation::SavePages {
+                start_page_no,
+                max_pages_to_write,
+                payload,
+            }
+        ),
+        (0..MAX_PAGES, payload, 1..4usize).prop_map(
+            |(start_page_no, payload, resize_pages_ratio)| {
+                PageBlobOperation::AutoResizeAndSavePages {
+                    start_page_no,
+                    payload,
+                    resize_pages_ratio,
+                }
+            }
+        ),
+        Just(PageBlobOperation::Download),
+        Just(PageBlobOperation::Delete),
+        Just(PageBlobOperation::DeleteIfExists),
+    ]
+}
+
+fn pad_to_full_pages(payload: &[u8]) -> Vec<u8> {
+    let mut result = payload.to_vec();
+    result.resize(payload.len().div_ceil(BLOB_PAGE_SIZE) * BLOB_PAGE_SIZE, 0);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::MyPageBlobMock;
+
+    fn check_mock(operations: &[PageBlobOperation]) -> Result<(), String> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let mut page_blob = MyPageBlobMock::new();
+            page_blob.create_container_if_not_exist().await.unwrap();
+            check_against_model(&mut page_blob, operations).await
+        })
+    }
+
+    #[test]
+    fn test_mock_matches_model_on_known_sequences() {
+        assert!(check_mock(&[
+            PageBlobOperation::Resize(1),
+            PageBlobOperation::Create(1),
+            PageBlobOperation::Resize(2),
+            PageBlobOperation::Download,
+        ])
+        .is_ok());
+
+        assert!(check_mock(&[
+            PageBlobOperation::Create(1),
+            PageBlobOperation::SavePages {
+                start_page_no: 1,
+                max_pages_to_write: 1,
+                payload: vec![1u8; 10],
+            },
+            PageBlobOperation::AutoResizeAndSavePages {
+                start_page_no: 2,
+                payload: vec![2u8; 10],
+                resize_pages_ratio: 2,
+            },
+            PageBlobOperation::Get {
+                start_page_no: 2,
+                pages_amount: 2,
+            },
+            PageBlobOperation::Delete,
+            PageBlobOperation::Delete,
+        ])
+        .is_ok());
+    }
+
+    proptest! {
+        #[test]
+        fn test_mock_matches_model(operations in prop::collection::vec(operation_strategy(), 1..30)) {
+            if let Err(err) = check_mock(&operations) {
+                prop_assert!(false, "{}", err);
+            }
+        }
+    }
+}
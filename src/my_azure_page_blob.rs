@@ -1,15 +1,26 @@
-use my_azure_storage_sdk::{blob::BlobProperties, AzureStorageConnection, AzureStorageError};
+use std::{collections::BTreeMap, ops::Range, sync::Arc};
+
+use my_azure_storage_sdk::{blob::BlobProperties, AzureStorageConnection};
 
 use async_trait::async_trait;
-use my_telemetry::MyTelemetryToConsole;
+use bytes::Bytes;
+use my_telemetry::{MyTelemetry, MyTelemetryToConsole};
 
-use crate::sdk::MyAzurePageBlobSdk;
+use crate::{
+    blob_rest::BlobRestConnection,
+    deadline::{execute_with_deadline, execute_write_with_deadline},
+    sdk::MyAzurePageBlobSdk,
+    GrowthStrategy, MyAzurePageBlobBuilder, PageBlobError, PageBlobOptions, PageRangeResult,
+    SequenceNumberAction, SequenceNumberCondition, WriteProgress,
+};
 
 use super::MyPageBlob;
 
-pub struct MyAzurePageBlob {
+pub struct MyAzurePageBlob<TMyTelemetry: MyTelemetry + Send + Sync + 'static = MyTelemetryToConsole>
+{
     sdk: MyAzurePageBlobSdk,
-    connection: AzureStorageConnection,
+    connection: Arc<AzureStorageConnection>,
+    telemetry: Option<Arc<TMyTelemetry>>,
 }
 
 impl MyAzurePageBlob {
@@ -18,15 +29,61 @@ impl MyAzurePageBlob {
         container_name: String,
         blob_name: String,
     ) -> Self {
+        Self::new_with_shared_connection(Arc::new(connection), container_name, blob_name)
+    }
+
+    pub fn new_with_shared_connection(
+        connection: Arc<AzureStorageConnection>,
+        container_name: String,
+        blob_name: String,
+    ) -> Self {
+        Self::builder(connection, container_name, blob_name).build()
+    }
+
+    pub fn new_with_options(
+        connection: Arc<AzureStorageConnection>,
+        container_name: String,
+        blob_name: String,
+        options: PageBlobOptions,
+    ) -> Self {
+        Self::builder(connection, container_name, blob_name)
+            .with_options(options)
+            .build()
+    }
+
+    pub fn builder(
+        connection: Arc<AzureStorageConnection>,
+        container_name: String,
+        blob_name: String,
+    ) -> MyAzurePageBlobBuilder<MyTelemetryToConsole> {
+        MyAzurePageBlobBuilder::new(connection, container_name, blob_name)
+    }
+}
+
+impl<TMyTelemetry: MyTelemetry + Send + Sync + 'static> MyAzurePageBlob<TMyTelemetry> {
+    pub(crate) fn from_builder(
+        connection: Arc<AzureStorageConnection>,
+        container_name: String,
+        blob_name: String,
+        options: PageBlobOptions,
+        rest_connection: Option<Arc<BlobRestConnection>>,
+        telemetry: Option<Arc<TMyTelemetry>>,
+    ) -> Self {
+        let rest_connection = rest_connection
+            .unwrap_or_else(|| Arc::new(BlobRestConnection::new(connection.clone())));
+
         Self {
-            sdk: MyAzurePageBlobSdk::new(container_name, blob_name),
+            sdk: MyAzurePageBlobSdk::new(container_name, blob_name, options, rest_connection),
             connection,
+            telemetry,
         }
     }
 }
 
 #[async_trait]
-impl MyPageBlob for MyAzurePageBlob {
+impl<TMyTelemetry: MyTelemetry + Send + Sync + 'static> MyPageBlob
+    for MyAzurePageBlob<TMyTelemetry>
+{
     fn get_blob_name(&self) -> &str {
         return self.sdk.blob_name.as_str();
     }
@@ -35,140 +92,378 @@ impl MyPageBlob for MyAzurePageBlob {
         return self.sdk.container_name.as_str();
     }
 
-    async fn resize(&mut self, pages_amount: usize) -> Result<(), AzureStorageError> {
-        self.sdk
-            .resize::<MyTelemetryToConsole>(
+    fn get_options(&self) -> &PageBlobOptions {
+        return &self.sdk.options;
+    }
+
+    async fn resize(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "resize",
+            operation_timeout,
+            self.sdk.resize(
                 self.connection.get_connection_info(),
-                None,
+                self.telemetry.clone(),
                 pages_amount,
-            )
-            .await
+            ),
+        )
+        .await;
     }
 
-    async fn create_container_if_not_exist(&mut self) -> Result<(), AzureStorageError> {
-        return self
-            .sdk
-            .create_container_if_not_exist::<MyTelemetryToConsole>(
+    async fn create_container_if_not_exist(&mut self) -> Result<(), PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "create_container_if_not_exist",
+            operation_timeout,
+            self.sdk.create_container_if_not_exist(
                 self.connection.get_connection_info(),
-                None,
-            )
-            .await;
+                self.telemetry.clone(),
+            ),
+        )
+        .await;
     }
 
-    async fn get_available_pages_amount(&mut self) -> Result<usize, AzureStorageError> {
-        return self
-            .sdk
-            .get_available_pages_amount::<MyTelemetryToConsole>(
+    async fn get_available_pages_amount(&mut self) -> Result<usize, PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "get_available_pages_amount",
+            operation_timeout,
+            self.sdk.get_available_pages_amount(
                 self.connection.get_connection_info(),
-                None,
-            )
-            .await;
+                self.telemetry.clone(),
+            ),
+        )
+        .await;
     }
 
-    async fn create(&mut self, pages_amount: usize) -> Result<(), AzureStorageError> {
-        return self
-            .sdk
-            .create::<MyTelemetryToConsole>(
+    async fn refresh_size(&mut self) -> Result<usize, PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "refresh_size",
+            operation_timeout,
+            self.sdk.read_blob_size(
+                self.connection.get_connection_info(),
+                self.telemetry.clone(),
+            ),
+        )
+        .await;
+    }
+
+    async fn create(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        if self.sdk.options.auto_create_container {
+            self.create_container_if_not_exist().await?;
+        }
+
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "create",
+            operation_timeout,
+            self.sdk.create(
                 self.connection.get_connection_info(),
-                None,
+                self.telemetry.clone(),
                 pages_amount,
-            )
-            .await;
+            ),
+        )
+        .await;
     }
 
-    async fn create_if_not_exists(&mut self, pages_amount: usize) -> Result<(), AzureStorageError> {
-        return self
-            .sdk
-            .create_if_not_exists::<MyTelemetryToConsole>(
+    async fn create_if_not_exists(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        if self.sdk.options.auto_create_container {
+            self.create_container_if_not_exist().await?;
+        }
+
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "create_if_not_exists",
+            operation_timeout,
+            self.sdk.create_if_not_exists(
                 self.connection.get_connection_info(),
-                None,
+                self.telemetry.clone(),
                 pages_amount,
-            )
-            .await;
+            ),
+        )
+        .await;
     }
 
     async fn get(
         &mut self,
         start_page_no: usize,
         pages_amount: usize,
-    ) -> Result<Vec<u8>, AzureStorageError> {
-        return self
-            .sdk
-            .get::<MyTelemetryToConsole>(
+    ) -> Result<Bytes, PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "get",
+            operation_timeout,
+            self.sdk.get(
                 self.connection.get_connection_info(),
-                None,
+                self.telemetry.clone(),
                 start_page_no,
                 pages_amount,
-            )
-            .await;
+            ),
+        )
+        .await;
+    }
+
+    async fn get_page_ranges_batch(
+        &mut self,
+        ranges: &[(usize, usize)],
+    ) -> Result<Vec<Bytes>, PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "get_page_ranges_batch",
+            operation_timeout,
+            self.sdk.get_page_ranges_batch(
+                self.connection.get_connection_info(),
+                self.telemetry.clone(),
+                ranges,
+            ),
+        )
+        .await;
     }
 
     async fn save_pages(
         &mut self,
         start_page_no: usize,
         max_pages_to_write: usize,
-        payload: Vec<u8>,
-    ) -> Result<usize, AzureStorageError> {
+        payload: Bytes,
+    ) -> Result<usize, PageBlobError> {
         return self
-            .sdk
-            .save_pages::<MyTelemetryToConsole>(
-                self.connection.get_connection_info(),
-                None,
+            .save_pages_with_progress(
                 start_page_no,
                 max_pages_to_write,
                 payload,
+                WriteProgress::new(),
             )
             .await;
     }
 
-    async fn auto_ressize_and_save_pages(
+    async fn save_pages_with_progress(
+        &mut self,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+        write_progress: WriteProgress,
+    ) -> Result<usize, PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_write_with_deadline(
+            "save_pages",
+            operation_timeout,
+            &write_progress,
+            self.sdk.save_pages(
+                self.connection.get_connection_info(),
+                self.telemetry.clone(),
+                start_page_no,
+                max_pages_to_write,
+                payload,
+                &write_progress,
+            ),
+        )
+        .await;
+    }
+
+    async fn auto_ressize_and_save_pages_with_growth(
         &mut self,
         start_page_no: usize,
         max_pages_to_write_single_round_trip: usize,
-        payload: Vec<u8>,
-        resize_pages_ratio: usize,
-    ) -> Result<usize, AzureStorageError> {
-        return self
-            .sdk
-            .auto_ressize_and_save_pages::<MyTelemetryToConsole>(
+        payload: Bytes,
+        growth_strategy: &dyn GrowthStrategy,
+    ) -> Result<usize, PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+        let write_progress = WriteProgress::new();
+
+        return execute_write_with_deadline(
+            "auto_ressize_and_save_pages",
+            operation_timeout,
+            &write_progress,
+            self.sdk.auto_ressize_and_save_pages(
                 self.connection.get_connection_info(),
-                None,
+                self.telemetry.clone(),
                 start_page_no,
                 max_pages_to_write_single_round_trip,
                 payload,
-                resize_pages_ratio,
-            )
-            .await;
+                growth_strategy,
+                &write_progress,
+            ),
+        )
+        .await;
     }
 
-    async fn delete(&mut self) -> Result<(), AzureStorageError> {
-        return self
-            .sdk
-            .delete::<MyTelemetryToConsole>(self.connection.get_connection_info(), None)
-            .await;
+    async fn delete(&mut self) -> Result<(), PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "delete",
+            operation_timeout,
+            self.sdk.delete(
+                self.connection.get_connection_info(),
+                self.telemetry.clone(),
+            ),
+        )
+        .await;
     }
 
-    async fn delete_if_exists(&mut self) -> Result<(), AzureStorageError> {
-        return self
-            .sdk
-            .delete_if_exists::<MyTelemetryToConsole>(self.connection.get_connection_info(), None)
-            .await;
+    async fn delete_if_exists(&mut self) -> Result<(), PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "delete_if_exists",
+            operation_timeout,
+            self.sdk.delete_if_exists(
+                self.connection.get_connection_info(),
+                self.telemetry.clone(),
+            ),
+        )
+        .await;
     }
 
-    async fn download(&mut self) -> Result<Vec<u8>, AzureStorageError> {
-        return self
-            .sdk
-            .download::<MyTelemetryToConsole>(self.connection.get_connection_info(), None)
-            .await;
+    async fn save_page_ranges(
+        &mut self,
+        ranges: Vec<(usize, Bytes)>,
+    ) -> Result<Vec<PageRangeResult>, PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "save_page_ranges",
+            operation_timeout,
+            self.sdk.save_page_ranges(
+                self.connection.get_connection_info(),
+                self.telemetry.clone(),
+                ranges,
+            ),
+        )
+        .await;
     }
 
-    async fn get_blob_properties(&mut self) -> Result<BlobProperties, AzureStorageError> {
-        return self
-            .sdk
-            .get_blob_properties::<MyTelemetryToConsole>(
+    async fn download(&mut self) -> Result<Bytes, PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "download",
+            operation_timeout,
+            self.sdk.download(
                 self.connection.get_connection_info(),
-                None,
-            )
+                self.telemetry.clone(),
+            ),
+        )
+        .await;
+    }
+
+    async fn get_blob_properties(&mut self) -> Result<BlobProperties, PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "get_blob_properties",
+            operation_timeout,
+            self.sdk.get_blob_properties(
+                self.connection.get_connection_info(),
+                self.telemetry.clone(),
+            ),
+        )
+        .await;
+    }
+
+    async fn get_sequence_number(&mut self) -> Result<u64, PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "get_sequence_number",
+            operation_timeout,
+            self.sdk.get_sequence_number(),
+        )
+        .await;
+    }
+
+    async fn update_sequence_number(
+        &mut self,
+        action: SequenceNumberAction,
+    ) -> Result<u64, PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "update_sequence_number",
+            operation_timeout,
+            self.sdk.update_sequence_number(action),
+        )
+        .await;
+    }
+
+    async fn resize_if_sequence_number(
+        &mut self,
+        condition: SequenceNumberCondition,
+        pages_amount: usize,
+    ) -> Result<(), PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "resize_if_sequence_number",
+            operation_timeout,
+            self.sdk.resize_if_sequence_number(condition, pages_amount),
+        )
+        .await;
+    }
+
+    async fn save_pages_if_sequence_number(
+        &mut self,
+        condition: SequenceNumberCondition,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+    ) -> Result<usize, PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "save_pages_if_sequence_number",
+            operation_timeout,
+            self.sdk.save_pages_if_sequence_number(
+                self.connection.get_connection_info(),
+                self.telemetry.clone(),
+                condition,
+                start_page_no,
+                max_pages_to_write,
+                payload,
+            ),
+        )
+        .await;
+    }
+
+    async fn get_written_page_ranges(&mut self) -> Result<Vec<Range<usize>>, PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "get_written_page_ranges",
+            operation_timeout,
+            self.sdk.get_written_page_ranges(),
+        )
+        .await;
+    }
+
+    async fn get_metadata(&mut self) -> Result<BTreeMap<String, String>, PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline("get_metadata", operation_timeout, self.sdk.get_metadata())
             .await;
     }
+
+    async fn set_metadata(
+        &mut self,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<(), PageBlobError> {
+        let operation_timeout = self.sdk.options.operation_timeout;
+
+        return execute_with_deadline(
+            "set_metadata",
+            operation_timeout,
+            self.sdk.set_metadata(&metadata),
+        )
+        .await;
+    }
 }
@@ -0,0 +1,120 @@
+use std::{sync::Arc, time::Duration};
+
+use my_azure_storage_sdk::AzureStorageConnection;
+use my_telemetry::MyTelemetry;
+
+use crate::{
+    blob_rest::BlobRestConnection, limits::clamp_max_pages_to_write, MyAzurePageBlob,
+    PageBlobOptions, PagesAvailableCachePolicy,
+};
+
+pub struct MyAzurePageBlobBuilder<TMyTelemetry: MyTelemetry + Send + Sync + 'static> {
+    connection: Arc<AzureStorageConnection>,
+    container_name: String,
+    blob_name: String,
+    options: PageBlobOptions,
+    rest_connection: Option<Arc<BlobRestConnection>>,
+    telemetry: Option<Arc<TMyTelemetry>>,
+}
+
+impl<TMyTelemetry: MyTelemetry + Send + Sync + 'static> MyAzurePageBlobBuilder<TMyTelemetry> {
+    pub fn new(
+        connection: Arc<AzureStorageConnection>,
+        container_name: String,
+        blob_name: String,
+    ) -> Self {
+        Self {
+            connection,
+            container_name,
+            blob_name,
+            options: PageBlobOptions::default(),
+            rest_connection: None,
+            telemetry: None,
+        }
+    }
+
+    pub fn with_options(mut self, options: PageBlobOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn with_max_pages_to_write(mut self, max_pages_to_write: usize) -> Self {
+        self.options.max_pages_to_write = clamp_max_pages_to_write(max_pages_to_write);
+        self
+    }
+
+    pub fn with_resize_pages_ratio(mut self, resize_pages_ratio: usize) -> Self {
+        self.options.resize_pages_ratio = resize_pages_ratio;
+        self
+    }
+
+    pub fn with_retries(mut self, retries: usize, retry_delay: Duration) -> Self {
+        self.options.retries = retries;
+        self.options.retry_delay = retry_delay;
+        self
+    }
+
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.options.request_timeout = Some(request_timeout);
+        self
+    }
+
+    pub fn with_operation_timeout(mut self, operation_timeout: Duration) -> Self {
+        self.options.operation_timeout = Some(operation_timeout);
+        self
+    }
+
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.options.max_concurrent_requests = max_concurrent_requests;
+        self
+    }
+
+    pub fn with_auto_create_container(mut self, auto_create_container: bool) -> Self {
+        self.options.auto_create_container = auto_create_container;
+        self
+    }
+
+    pub fn with_pages_available_cache(
+        mut self,
+        pages_available_cache: PagesAvailableCachePolicy,
+    ) -> Self {
+        self.options.pages_available_cache = pages_available_cache;
+        self
+    }
+
+    pub fn with_max_read_gap_pages(mut self, max_read_gap_pages: usize) -> Self {
+        self.options.max_read_gap_pages = max_read_gap_pages;
+        self
+    }
+
+    // Blobs opened by a container share its Blob REST client
+    pub(crate) fn with_rest_connection(mut self, rest_connection: Arc<BlobRestConnection>) -> Self {
+        self.rest_connection = Some(rest_connection);
+        self
+    }
+
+    pub fn with_telemetry<TNewTelemetry: MyTelemetry + Send + Sync + 'static>(
+        self,
+        telemetry: Arc<TNewTelemetry>,
+    ) -> MyAzurePageBlobBuilder<TNewTelemetry> {
+        MyAzurePageBlobBuilder {
+            connection: self.connection,
+            container_name: self.container_name,
+            blob_name: self.blob_name,
+            options: self.options,
+            rest_connection: self.rest_connection,
+            telemetry: Some(telemetry),
+        }
+    }
+
+    pub fn build(self) -> MyAzurePageBlob<TMyTelemetry> {
+        MyAzurePageBlob::from_builder(
+            self.connection,
+            self.container_name,
+            self.blob_name,
+            self.options,
+            self.rest_connection,
+            self.telemetry,
+        )
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use my_azure_storage_sdk::{AzureStorageConnection, AzureStorageError};
+use my_telemetry::MyTelemetryToConsole;
+
+use crate::{
+    blob_rest::BlobRestConnection, deadline::execute_with_deadline,
+    page_blob_container::filter_by_prefix, sdk::execute_round_trip, MyAzurePageBlob,
+    PageBlobContainer, PageBlobError, PageBlobOptions, PageBlobsSegment,
+};
+
+const LIST_SEGMENT_SIZE: usize = 100;
+
+pub struct MyAzurePageBlobContainer {
+    connection: Arc<AzureStorageConnection>,
+    container_name: String,
+    options: PageBlobOptions,
+    rest_connection: Arc<BlobRestConnection>,
+}
+
+impl MyAzurePageBlobContainer {
+    pub fn new(connection: Arc<AzureStorageConnection>, container_name: String) -> Self {
+        Self {
+            rest_connection: Arc::new(BlobRestConnection::new(connection.clone())),
+            connection,
+            container_name,
+            options: PageBlobOptions::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: PageBlobOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+#[async_trait]
+impl PageBlobContainer for MyAzurePageBlobContainer {
+    type TPageBlob = MyAzurePageBlob;
+
+    fn get_container_name(&self) -> &str {
+        return self.container_name.as_str();
+    }
+
+    async fn exists(&self) -> Result<bool, PageBlobError> {
+        let result = execute_with_deadline(
+            "exists",
+            self.options.operation_timeout,
+            execute_round_trip(&self.options, || {
+                self.rest_connection
+                    .get_container_properties(self.container_name.as_str())
+            }),
+        )
+        .await;
+
+        match result {
+            Ok(()) => Ok(true),
+            Err(PageBlobError::Azure(AzureStorageError::ContainerNotFound)) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn create_if_not_exists(&self) -> Result<(), PageBlobError> {
+        execute_with_deadline(
+            "create_if_not_exists",
+            self.options.operation_timeout,
+            execute_round_trip(&self.options, || {
+                my_azure_storage_sdk::blob_container::sdk::create_container_if_not_exist::<
+                    MyTelemetryToConsole,
+                >(
+                    self.connection.get_connection_info(),
+                    self.container_name.as_str(),
+                    None,
+                )
+            }),
+        )
+        .await
+    }
+
+    async fn delete_container(&self) -> Result<(), PageBlobError> {
+        execute_with_deadline(
+            "delete_container",
+            self.options.operation_timeout,
+            execute_round_trip(&self.options, || {
+                my_azure_storage_sdk::blob_container::sdk::delete_container::<MyTelemetryToConsole>(
+                    self.connection.get_connection_info(),
+                    self.container_name.as_str(),
+                    None,
+                )
+            }),
+        )
+        .await
+    }
+
+    async fn list_blobs(&self, prefix: Option<&str>) -> Result<Vec<String>, PageBlobError> {
+        let blobs =
+            execute_with_deadline(
+                "list_blobs",
+                self.options.operation_timeout,
+                execute_round_trip(&self.options, || {
+                    my_azure_storage_sdk::blob_container::sdk::get_list_of_blobs::<
+                        MyTelemetryToConsole,
+                    >(
+                        self.connection.get_connection_info(),
+                        self.container_name.as_str(),
+                        None,
+                    )
+                }),
+            )
+            .await?;
+
+        Ok(filter_by_prefix(blobs, prefix))
+    }
+
+    async fn list_page_blobs_segment(
+        &self,
+        prefix: Option<&str>,
+        continuation_token: Option<String>,
+    ) -> Result<PageBlobsSegment, PageBlobError> {
+        execute_with_deadline(
+            "list_page_blobs_segment",
+            self.options.operation_timeout,
+            execute_round_trip(&self.options, || {
+                self.rest_connection.list_page_blobs_segment(
+                    self.container_name.as_str(),
+                    prefix,
+                    continuation_token.as_deref(),
+                    LIST_SEGMENT_SIZE,
+                )
+            }),
+        )
+        .await
+    }
+
+    fn open_blob(&self, blob_name: &str) -> MyAzurePageBlob {
+        MyAzurePageBlob::builder(
+            self.connection.clone(),
+            self.container_name.clone(),
+            blob_name.to_string(),
+        )
+        .with_options(self.options.clone())
+        .with_rest_connection(self.rest_connection.clone())
+        .build()
+    }
+}
@@ -1,45 +1,201 @@
-use my_azure_storage_sdk::{blob::BlobProperties, AzureStorageError};
+use std::{collections::BTreeMap, ops::Range};
+
+use my_azure_storage_sdk::blob::BlobProperties;
 
 use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+
+use crate::{
+    FixedMultipleGrowth, GrowthStrategy, PageBlobError, PageBlobOptions, PageRangeResult,
+    SequenceNumberAction, SequenceNumberCondition, WriteProgress,
+};
 
 #[async_trait]
 pub trait MyPageBlob {
     fn get_container_name(&self) -> &str;
     fn get_blob_name(&self) -> &str;
+    fn get_options(&self) -> &PageBlobOptions;
 
-    async fn get_blob_properties(&mut self) -> Result<BlobProperties, AzureStorageError>;
+    async fn get_blob_properties(&mut self) -> Result<BlobProperties, PageBlobError>;
 
-    async fn create(&mut self, pages_amount: usize) -> Result<(), AzureStorageError>;
-    async fn create_if_not_exists(&mut self, pages_amount: usize) -> Result<(), AzureStorageError>;
-    async fn get_available_pages_amount(&mut self) -> Result<usize, AzureStorageError>;
-    async fn create_container_if_not_exist(&mut self) -> Result<(), AzureStorageError>;
+    async fn create(&mut self, pages_amount: usize) -> Result<(), PageBlobError>;
+    async fn create_if_not_exists(&mut self, pages_amount: usize) -> Result<(), PageBlobError>;
+    async fn get_available_pages_amount(&mut self) -> Result<usize, PageBlobError>;
+    // Reads the blob size ignoring the cached value
+    async fn refresh_size(&mut self) -> Result<usize, PageBlobError>;
+    async fn create_container_if_not_exist(&mut self) -> Result<(), PageBlobError>;
 
-    async fn resize(&mut self, pages_amount: usize) -> Result<(), AzureStorageError>;
+    async fn resize(&mut self, pages_amount: usize) -> Result<(), PageBlobError>;
 
-    async fn delete(&mut self) -> Result<(), AzureStorageError>;
+    async fn delete(&mut self) -> Result<(), PageBlobError>;
 
-    async fn delete_if_exists(&mut self) -> Result<(), AzureStorageError>;
+    async fn delete_if_exists(&mut self) -> Result<(), PageBlobError>;
 
     async fn get(
         &mut self,
         start_page_no: usize,
         pages_amount: usize,
-    ) -> Result<Vec<u8>, AzureStorageError>;
+    ) -> Result<Bytes, PageBlobError>;
+
+    // Nearby ranges are coalesced into a single request and read concurrently.
+    // Payloads are returned in the order of the ranges
+    async fn get_page_ranges_batch(
+        &mut self,
+        ranges: &[(usize, usize)],
+    ) -> Result<Vec<Bytes>, PageBlobError>;
 
     async fn save_pages(
         &mut self,
         start_page_no: usize,
         max_pages_to_write: usize,
-        mut payload: Vec<u8>,
-    ) -> Result<usize, AzureStorageError>;
+        mut payload: Bytes,
+    ) -> Result<usize, PageBlobError>;
+
+    // Committed bytes are reported to write_progress as chunks are written,
+    // so they are known even if the future is cancelled
+    async fn save_pages_with_progress(
+        &mut self,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        mut payload: Bytes,
+        write_progress: WriteProgress,
+    ) -> Result<usize, PageBlobError>;
+
+    async fn auto_ressize_and_save_pages_with_growth(
+        &mut self,
+        start_page_no: usize,
+        max_pages_to_write_single_round_trip: usize,
+        mut payload: Bytes,
+        growth_strategy: &dyn GrowthStrategy,
+    ) -> Result<usize, PageBlobError>;
 
     async fn auto_ressize_and_save_pages(
         &mut self,
         start_page_no: usize,
         max_pages_to_write_single_round_trip: usize,
-        mut payload: Vec<u8>,
-        resize_pages_ration: usize,
-    ) -> Result<usize, AzureStorageError>;
+        payload: Bytes,
+        resize_pages_ratio: usize,
+    ) -> Result<usize, PageBlobError>
+    where
+        Self: Send,
+    {
+        let growth_strategy = FixedMultipleGrowth::new(resize_pages_ratio);
+        return self
+            .auto_ressize_and_save_pages_with_growth(
+                start_page_no,
+                max_pages_to_write_single_round_trip,
+                payload,
+                &growth_strategy,
+            )
+            .await;
+    }
+
+    async fn save(&mut self, start_page_no: usize, payload: Bytes) -> Result<usize, PageBlobError>
+    where
+        Self: Send,
+    {
+        let max_pages_to_write = self.get_options().max_pages_to_write;
+        return self
+            .save_pages(start_page_no, max_pages_to_write, payload)
+            .await;
+    }
+
+    async fn auto_ressize_and_save(
+        &mut self,
+        start_page_no: usize,
+        payload: Bytes,
+    ) -> Result<usize, PageBlobError>
+    where
+        Self: Send,
+    {
+        let max_pages_to_write = self.get_options().max_pages_to_write;
+        let resize_pages_ratio = self.get_options().resize_pages_ratio;
+        return self
+            .auto_ressize_and_save_pages(
+                start_page_no,
+                max_pages_to_write,
+                payload,
+                resize_pages_ratio,
+            )
+            .await;
+    }
+
+    // Validates all ranges against the blob size before writing, merges adjacent ranges
+    // and writes them concurrently. Results are in the order of the ranges
+    async fn save_page_ranges(
+        &mut self,
+        ranges: Vec<(usize, Bytes)>,
+    ) -> Result<Vec<PageRangeResult>, PageBlobError>;
+
+    async fn download(&mut self) -> Result<Bytes, PageBlobError>;
 
-    async fn download(&mut self) -> Result<Vec<u8>, AzureStorageError>;
+    // Yields the blob in order by chunks of chunk_pages, so only a single chunk is kept in memory
+    fn download_stream(&mut self, chunk_pages: usize) -> BoxStream<'_, Result<Bytes, PageBlobError>>
+    where
+        Self: Send,
+    {
+        let chunk_pages = chunk_pages.max(1);
+
+        // The blob size is read by the first chunk
+        let stream = stream::try_unfold(
+            (self, None, 0),
+            move |(page_blob, pages_amount, start_page_no)| async move {
+                let pages_amount = match pages_amount {
+                    Some(pages_amount) => pages_amount,
+                    None => page_blob.refresh_size().await?,
+                };
+
+                if start_page_no >= pages_amount {
+                    return Ok(None);
+                }
+
+                let pages_to_read = chunk_pages.min(pages_amount - start_page_no);
+                let chunk = page_blob.get(start_page_no, pages_to_read).await?;
+
+                Ok(Some((
+                    chunk,
+                    (page_blob, Some(pages_amount), start_page_no + pages_to_read),
+                )))
+            },
+        );
+
+        return stream.boxed();
+    }
+
+    async fn get_sequence_number(&mut self) -> Result<u64, PageBlobError>;
+
+    async fn update_sequence_number(
+        &mut self,
+        action: SequenceNumberAction,
+    ) -> Result<u64, PageBlobError>;
+
+    async fn resize_if_sequence_number(
+        &mut self,
+        condition: SequenceNumberCondition,
+        pages_amount: usize,
+    ) -> Result<(), PageBlobError>;
+
+    async fn save_pages_if_sequence_number(
+        &mut self,
+        condition: SequenceNumberCondition,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        mut payload: Bytes,
+    ) -> Result<usize, PageBlobError>;
+
+    // Pages written since the blob is created, including pages written with zeros. Ranges are ordered
+    // and adjacent ranges are merged. Other pages were never written and read as zeros
+    async fn get_written_page_ranges(&mut self) -> Result<Vec<Range<usize>>, PageBlobError>;
+
+    async fn get_metadata(&mut self) -> Result<BTreeMap<String, String>, PageBlobError>;
+
+    // Replaces all metadata of the blob. Azure treats names as case insensitive C# identifiers
+    async fn set_metadata(
+        &mut self,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<(), PageBlobError>;
 }
@@ -0,0 +1,96 @@
+use crate::{MyPageBlob, PageBlobError};
+use async_trait::async_trait;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageBlobInfo {
+    pub name: String,
+    pub blob_size: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct PageBlobsSegment {
+    pub blobs: Vec<PageBlobInfo>,
+    pub continuation_token: Option<String>,
+}
+
+#[async_trait]
+pub trait PageBlobContainer {
+    type TPageBlob: MyPageBlob + Send + Sync;
+
+    fn get_container_name(&self) -> &str;
+
+    async fn exists(&self) -> Result<bool, PageBlobError>;
+
+    async fn create_if_not_exists(&self) -> Result<(), PageBlobError>;
+
+    async fn delete_container(&self) -> Result<(), PageBlobError>;
+
+    async fn list_blobs(&self, prefix: Option<&str>) -> Result<Vec<String>, PageBlobError>;
+
+    async fn list_page_blobs_segment(
+        &self,
+        prefix: Option<&str>,
+        continuation_token: Option<String>,
+    ) -> Result<PageBlobsSegment, PageBlobError>;
+
+    fn open_blob(&self, blob_name: &str) -> Self::TPageBlob;
+}
+
+pub fn filter_by_prefix(blobs: Vec<String>, prefix: Option<&str>) -> Vec<String> {
+    match prefix {
+        Some(prefix) => blobs
+            .into_iter()
+            .filter(|blob_name| blob_name.starts_with(prefix))
+            .collect(),
+        None => blobs,
+    }
+}
+
+// Continuation token is the name of the last blob returned by the previous segment
+pub fn get_segment_range(
+    sorted_blob_names: &[String],
+    continuation_token: Option<&str>,
+    segment_size: usize,
+) -> (usize, usize, Option<String>) {
+    let start = match continuation_token {
+        Some(token) => sorted_blob_names.partition_point(|blob_name| blob_name.as_str() <= token),
+        None => 0,
+    };
+
+    let end = sorted_blob_names.len().min(start + segment_size.max(1));
+
+    if end < sorted_blob_names.len() {
+        return (start, end, Some(sorted_blob_names[end - 1].to_string()));
+    }
+
+    (start, end, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_segment_range() {
+        let blobs: Vec<String> = vec!["a/1", "a/2", "a/3", "b/1", "b/2"]
+            .into_iter()
+            .map(|itm| itm.to_string())
+            .collect();
+
+        assert_eq!(
+            (0, 2, Some("a/2".to_string())),
+            get_segment_range(&blobs, None, 2)
+        );
+        assert_eq!(
+            (2, 4, Some("b/1".to_string())),
+            get_segment_range(&blobs, Some("a/2"), 2)
+        );
+        assert_eq!((4, 5, None), get_segment_range(&blobs, Some("b/1"), 2));
+        assert_eq!((5, 5, None), get_segment_range(&blobs, Some("b/2"), 2));
+        assert_eq!((0, 5, None), get_segment_range(&blobs, None, 10));
+        assert_eq!(
+            (0, 1, Some("a/1".to_string())),
+            get_segment_range(&blobs, None, 0)
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub query: BTreeMap<String, String>,
+    // Header names are lowercased
+    pub headers: BTreeMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(|value| value.as_str())
+    }
+
+    pub fn get_query(&self, name: &str) -> Option<&str> {
+        self.query.get(name).map(|value| value.as_str())
+    }
+}
+
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    // HEAD responses report the length of the content they do not send
+    pub content_length: Option<usize>,
+}
+
+impl HttpResponse {
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+            content_length: None,
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: String) -> Self {
+        self.headers.push((name.to_string(), value));
+        self
+    }
+
+    pub fn with_body(mut self, body: Vec<u8>) -> Self {
+        self.body = body;
+        self
+    }
+
+    pub fn with_content_length(mut self, content_length: usize) -> Self {
+        self.content_length = Some(content_length);
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum ReadRequestError {
+    // Body is left unread, so the connection can not be reused
+    BodyTooLarge,
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for ReadRequestError {
+    fn from(src: std::io::Error) -> Self {
+        Self::Io(src)
+    }
+}
+
+pub async fn read_request<TReader: AsyncBufRead + Unpin>(
+    reader: &mut TReader,
+    max_body_size: usize,
+) -> Result<Option<HttpRequest>, ReadRequestError> {
+    let mut line = String::new();
+
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+
+    let mut parts = line.split_whitespace();
+
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+        _ => return Err(invalid_data(format!("Invalid request line: {}", line.trim())).into()),
+    };
+
+    let mut headers = BTreeMap::new();
+
+    loop {
+        line.clear();
+
+        if reader.read_line(&mut line).await? == 0 {
+            return Err(invalid_data("Connection is closed before headers end".to_string()).into());
+        }
+
+        let header = line.trim_end();
+
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let content_length = match headers.get("content-length") {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| invalid_data(format!("Invalid Content-Length: {}", value)))?,
+        None => 0,
+    };
+
+    if content_length > max_body_size {
+        return Err(ReadRequestError::BodyTooLarge);
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).await?;
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path.to_string(), parse_query(query)),
+        None => (target, BTreeMap::new()),
+    };
+
+    Ok(Some(HttpRequest {
+        method,
+        path: decode_uri_component(&path),
+        query,
+        headers,
+        body,
+    }))
+}
+
+pub async fn write_response<TWriter: AsyncWrite + Unpin>(
+    writer: &mut TWriter,
+    response: &HttpResponse,
+) -> std::io::Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\n",
+        response.status,
+        get_reason_phrase(response.status)
+    );
+
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+
+    head.push_str(&format!(
+        "Content-Length: {}\r\n\r\n",
+        response.content_length.unwrap_or(response.body.len())
+    ));
+
+    writer.write_all(head.as_bytes()).await?;
+    writer.write_all(&response.body).await?;
+    writer.flush().await
+}
+
+fn parse_query(query: &str) -> BTreeMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((name, value)) => (decode_uri_component(name), decode_uri_component(value)),
+            None => (decode_uri_component(pair), String::new()),
+        })
+        .collect()
+}
+
+pub fn decode_uri_component(src: &str) -> String {
+    let src = src.as_bytes();
+    let mut result = Vec::with_capacity(src.len());
+    let mut index = 0;
+
+    while index < src.len() {
+        if src[index] == b'%' && index + 2 < src.len() {
+            let hex = std::str::from_utf8(&src[index + 1..index + 3]).unwrap_or("");
+
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                result.push(byte);
+                index += 3;
+                continue;
+            }
+        }
+
+        result.push(src[index]);
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&result).to_string()
+}
+
+fn get_reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        206 => "Partial Content",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        412 => "Precondition Failed",
+        413 => "Request Entity Too Large",
+        416 => "Requested Range Not Satisfiable",
+        _ => "Internal Server Error",
+    }
+}
+
+fn invalid_data(msg: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_request() {
+        let src = b"PUT /devstoreaccount1/container/tenant%2F1?comp=page&x=a%20b HTTP/1.1\r\nx-ms-range: bytes=0-3\r\nContent-Length: 4\r\n\r\ntest";
+        let mut reader = tokio::io::BufReader::new(&src[..]);
+
+        let request = read_request(&mut reader, 4).await.unwrap().unwrap();
+
+        assert_eq!("PUT", request.method);
+        assert_eq!("/devstoreaccount1/container/tenant/1", request.path);
+        assert_eq!(Some("page"), request.get_query("comp"));
+        assert_eq!(Some("a b"), request.get_query("x"));
+        assert_eq!(Some("bytes=0-3"), request.get_header("x-ms-range"));
+        assert_eq!(b"test".to_vec(), request.body);
+
+        assert!(read_request(&mut reader, 4).await.unwrap().is_none());
+
+        let src = b"PUT /devstoreaccount1/container/blob?comp=page HTTP/1.1\r\nContent-Length: 5\r\n\r\ntest!";
+        let mut reader = tokio::io::BufReader::new(&src[..]);
+
+        assert!(matches!(
+            read_request(&mut reader, 4).await,
+            Err(ReadRequestError::BodyTooLarge)
+        ));
+    }
+}
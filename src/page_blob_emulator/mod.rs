@@ -0,0 +1,1248 @@
+mod http;
+mod storage;
+
+use std::{collections::BTreeMap, net::SocketAddr, path::PathBuf, sync::Arc};
+
+use my_azure_storage_sdk::page_blob::consts::BLOB_PAGE_SIZE;
+use tokio::{
+    io::BufReader,
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+};
+
+use crate::{MAX_BLOB_SIZE, MAX_PUT_PAGE_SIZE, MAX_SEQUENCE_NUMBER};
+
+use self::{
+    http::{HttpRequest, HttpResponse, ReadRequestError},
+    storage::{BlobAttributes, DiskStorage, EmulatorStorage, MemoryStorage},
+};
+
+pub const EMULATOR_ACCOUNT_NAME: &str = "devstoreaccount1";
+// Well known development storage key. The emulator does not validate signatures.
+pub const EMULATOR_ACCOUNT_KEY: &str =
+    "Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw==";
+
+// Bodies are pages or small XML documents, anything larger is rejected before it is read
+const MAX_REQUEST_BODY_SIZE: usize = MAX_PUT_PAGE_SIZE + 64 * 1024;
+const DEFAULT_MAX_RESULTS: usize = 5000;
+const METADATA_HEADER_PREFIX: &str = "x-ms-meta-";
+
+#[derive(Debug)]
+pub struct EmulatorError {
+    pub status: u16,
+    pub code: &'static str,
+}
+
+impl EmulatorError {
+    fn new(status: u16, code: &'static str) -> Self {
+        Self { status, code }
+    }
+
+    pub fn container_not_found() -> Self {
+        Self::new(404, "ContainerNotFound")
+    }
+
+    pub fn container_already_exists() -> Self {
+        Self::new(409, "ContainerAlreadyExists")
+    }
+
+    pub fn blob_not_found() -> Self {
+        Self::new(404, "BlobNotFound")
+    }
+
+    pub fn blob_already_exists() -> Self {
+        Self::new(409, "BlobAlreadyExists")
+    }
+
+    pub fn invalid_page_range() -> Self {
+        Self::new(416, "InvalidPageRange")
+    }
+
+    pub fn invalid_range() -> Self {
+        Self::new(416, "InvalidRange")
+    }
+
+    pub fn invalid_header_value() -> Self {
+        Self::new(400, "InvalidHeaderValue")
+    }
+
+    pub fn missing_required_header() -> Self {
+        Self::new(400, "MissingRequiredHeader")
+    }
+
+    pub fn request_body_too_large() -> Self {
+        Self::new(413, "RequestBodyTooLarge")
+    }
+
+    pub fn condition_not_met() -> Self {
+        Self::new(412, "ConditionNotMet")
+    }
+
+    pub fn sequence_number_condition_not_met() -> Self {
+        Self::new(412, "SequenceNumberConditionNotMet")
+    }
+
+    pub fn invalid_query_parameter_value() -> Self {
+        Self::new(400, "InvalidQueryParameterValue")
+    }
+
+    pub fn unsupported_operation() -> Self {
+        Self::new(405, "UnsupportedHttpVerb")
+    }
+}
+
+impl From<std::io::Error> for EmulatorError {
+    fn from(_: std::io::Error) -> Self {
+        Self::new(500, "InternalError")
+    }
+}
+
+// Speaks the subset of the Blob REST API used by my-azure-storage-sdk for page blobs.
+// Both path style (/account/container/blob) and host style (/container/blob) urls are accepted.
+pub struct PageBlobEmulator {
+    storage: Arc<Mutex<Box<dyn EmulatorStorage>>>,
+}
+
+impl PageBlobEmulator {
+    pub fn new_in_memory() -> Self {
+        Self {
+            storage: Arc::new(Mutex::new(Box::new(MemoryStorage::new()))),
+        }
+    }
+
+    pub fn new_on_disk(root: PathBuf) -> Result<Self, EmulatorError> {
+        let storage = DiskStorage::new(root)?;
+
+        Ok(Self {
+            storage: Arc::new(Mutex::new(Box::new(storage))),
+        })
+    }
+
+    // Binds to the address and serves requests in the background. Use port 0 to pick a free port.
+    pub async fn start(self, addr: &str) -> std::io::Result<SocketAddr> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+
+        tokio::spawn(async move {
+            if let Err(err) = self.run(listener).await {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?err, "Page blob emulator is stopped");
+                #[cfg(not(feature = "tracing"))]
+                let _ = err;
+            }
+        });
+
+        Ok(local_addr)
+    }
+
+    pub async fn run(self, listener: TcpListener) -> std::io::Result<()> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let storage = self.storage.clone();
+
+            tokio::spawn(async move {
+                if let Err(err) = serve_connection(storage, stream).await {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(?err, "Page blob emulator connection is closed");
+                    #[cfg(not(feature = "tracing"))]
+                    let _ = err;
+                }
+            });
+        }
+    }
+}
+
+pub fn get_emulator_connection_string(addr: SocketAddr) -> String {
+    format!(
+        "DefaultEndpointsProtocol=http;AccountName={};AccountKey={};BlobEndpoint=http://{}/{};",
+        EMULATOR_ACCOUNT_NAME, EMULATOR_ACCOUNT_KEY, addr, EMULATOR_ACCOUNT_NAME
+    )
+}
+
+async fn serve_connection(
+    storage: Arc<Mutex<Box<dyn EmulatorStorage>>>,
+    stream: TcpStream,
+) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    loop {
+        let request = match http::read_request(&mut reader, MAX_REQUEST_BODY_SIZE).await {
+            Ok(Some(request)) => request,
+            Ok(None) => break,
+            Err(ReadRequestError::BodyTooLarge) => {
+                let response = get_error_response(EmulatorError::request_body_too_large())
+                    .with_header("Connection", "close".to_string());
+                http::write_response(&mut writer, &response).await?;
+                break;
+            }
+            Err(ReadRequestError::Io(err)) => return Err(err),
+        };
+
+        let response = {
+            let mut storage = storage.lock().await;
+            handle_request(&mut **storage, &request)
+        };
+
+        http::write_response(&mut writer, &response).await?;
+
+        if request.get_header("connection") == Some("close") {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_request(storage: &mut dyn EmulatorStorage, request: &HttpRequest) -> HttpResponse {
+    let is_head = request.method == "HEAD";
+
+    let response = match route_request(storage, request) {
+        Ok(response) => response,
+        Err(err) => get_error_response(err),
+    };
+
+    if is_head {
+        let content_length = response.content_length.unwrap_or(response.body.len());
+        return response
+            .with_body(Vec::new())
+            .with_content_length(content_length);
+    }
+
+    response
+}
+
+fn get_error_response(err: EmulatorError) -> HttpResponse {
+    let body = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?><Error><Code>{}</Code><Message>{}</Message></Error>",
+        err.code, err.code
+    );
+
+    HttpResponse::new(err.status)
+        .with_header("x-ms-error-code", err.code.to_string())
+        .with_header("Content-Type", "application/xml".to_string())
+        .with_body(body.into_bytes())
+}
+
+fn route_request(
+    storage: &mut dyn EmulatorStorage,
+    request: &HttpRequest,
+) -> Result<HttpResponse, EmulatorError> {
+    let mut segments: Vec<&str> = request
+        .path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    if segments.first() == Some(&EMULATOR_ACCOUNT_NAME) {
+        segments.remove(0);
+    }
+
+    let comp = request.get_query("comp");
+    let restype = request.get_query("restype");
+
+    if segments.is_empty() {
+        return match (request.method.as_str(), comp) {
+            ("GET", Some("list")) => list_containers(storage, request),
+            _ => Err(EmulatorError::unsupported_operation()),
+        };
+    }
+
+    let container = segments[0];
+
+    if segments.len() == 1 {
+        return match (request.method.as_str(), restype, comp) {
+            ("PUT", Some("container"), None) => {
+                storage.create_container(container)?;
+                Ok(HttpResponse::new(201))
+            }
+            ("DELETE", Some("container"), None) => {
+                storage.delete_container(container)?;
+                Ok(HttpResponse::new(202))
+            }
+            ("GET" | "HEAD", Some("container"), None) => {
+                if !storage.container_exists(container)? {
+                    return Err(EmulatorError::container_not_found());
+                }
+
+                Ok(HttpResponse::new(200))
+            }
+            ("GET", Some("container"), Some("list")) => list_blobs(storage, container, request),
+            _ => Err(EmulatorError::unsupported_operation()),
+        };
+    }
+
+    let blob = segments[1..].join("/");
+
+    match (request.method.as_str(), comp) {
+        ("PUT", None) => put_blob(storage, container, &blob, request),
+        ("PUT", Some("page")) => put_page(storage, container, &blob, request),
+        ("PUT", Some("properties")) => set_blob_properties(storage, container, &blob, request),
+        ("PUT", Some("metadata")) => set_blob_metadata(storage, container, &blob, request),
+        ("GET", Some("pagelist")) => get_page_ranges(storage, container, &blob, request),
+        ("GET" | "HEAD", Some("metadata")) => {
+            storage.get_blob_size(container, &blob)?;
+            let attributes = storage.get_blob_attributes(container, &blob)?;
+            check_if_match(request, &attributes)?;
+
+            Ok(with_metadata(
+                with_etag(HttpResponse::new(200), &attributes),
+                &attributes,
+            ))
+        }
+        ("GET", None) => get_blob(storage, container, &blob, request),
+        ("HEAD", None) => {
+            let blob_size = storage.get_blob_size(container, &blob)?;
+            let attributes = storage.get_blob_attributes(container, &blob)?;
+            check_if_match(request, &attributes)?;
+
+            if request.get_header("if-none-match") == Some(get_etag(&attributes).as_str()) {
+                return Ok(with_etag(HttpResponse::new(304), &attributes));
+            }
+
+            Ok(
+                with_blob_headers(HttpResponse::new(200), blob_size, &attributes)
+                    .with_content_length(blob_size),
+            )
+        }
+        ("DELETE", None) => {
+            storage.delete_blob(container, &blob)?;
+            Ok(HttpResponse::new(202))
+        }
+        _ => Err(EmulatorError::unsupported_operation()),
+    }
+}
+
+fn list_containers(
+    storage: &mut dyn EmulatorStorage,
+    request: &HttpRequest,
+) -> Result<HttpResponse, EmulatorError> {
+    let prefix = request.get_query("prefix").unwrap_or("");
+    let mut xml =
+        String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?><EnumerationResults><Containers>");
+
+    for container in storage.list_containers()? {
+        if container.starts_with(prefix) {
+            xml.push_str(&format!(
+                "<Container><Name>{}</Name></Container>",
+                escape_xml(&container)
+            ));
+        }
+    }
+
+    xml.push_str("</Containers><NextMarker /></EnumerationResults>");
+    Ok(xml_response(xml))
+}
+
+fn list_blobs(
+    storage: &mut dyn EmulatorStorage,
+    container: &str,
+    request: &HttpRequest,
+) -> Result<HttpResponse, EmulatorError> {
+    let prefix = request.get_query("prefix").unwrap_or("");
+    // Marker is the name of the last blob of the previous segment
+    let marker = request.get_query("marker").unwrap_or("");
+    let max_results = match request.get_query("maxresults") {
+        Some(value) => value
+            .parse::<usize>()
+            .ok()
+            .filter(|max_results| *max_results > 0)
+            .ok_or_else(EmulatorError::invalid_query_parameter_value)?,
+        None => DEFAULT_MAX_RESULTS,
+    };
+
+    let blobs: Vec<(String, usize)> = storage
+        .list_blobs(container)?
+        .into_iter()
+        .filter(|(blob, _)| blob.starts_with(prefix) && blob.as_str() > marker)
+        .collect();
+
+    let mut xml =
+        String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?><EnumerationResults><Blobs>");
+
+    for (blob, blob_size) in blobs.iter().take(max_results) {
+        xml.push_str(&format!(
+            "<Blob><Name>{}</Name><Properties><Content-Length>{}</Content-Length><BlobType>PageBlob</BlobType></Properties></Blob>",
+            escape_xml(blob),
+            blob_size
+        ));
+    }
+
+    xml.push_str("</Blobs>");
+
+    if blobs.len() > max_results {
+        xml.push_str(&format!(
+            "<NextMarker>{}</NextMarker>",
+            escape_xml(&blobs[max_results - 1].0)
+        ));
+    } else {
+        xml.push_str("<NextMarker />");
+    }
+
+    xml.push_str("</EnumerationResults>");
+    Ok(xml_response(xml))
+}
+
+fn put_blob(
+    storage: &mut dyn EmulatorStorage,
+    container: &str,
+    blob: &str,
+    request: &HttpRequest,
+) -> Result<HttpResponse, EmulatorError> {
+    match request.get_header("x-ms-blob-type") {
+        Some("PageBlob") => {}
+        Some(_) => return Err(EmulatorError::invalid_header_value()),
+        None => return Err(EmulatorError::missing_required_header()),
+    }
+
+    let blob_size =
+        get_blob_content_length(request)?.ok_or_else(EmulatorError::missing_required_header)?;
+
+    if !storage.container_exists(container)? {
+        return Err(EmulatorError::container_not_found());
+    }
+
+    if request.get_header("if-none-match") == Some("*")
+        && storage.get_blob_size(container, blob).is_ok()
+    {
+        return Err(EmulatorError::blob_already_exists());
+    }
+
+    let version = match storage.get_blob_attributes(container, blob) {
+        Ok(attributes) => attributes.version + 1,
+        Err(_) => 1,
+    };
+
+    storage.create_blob(container, blob, blob_size)?;
+
+    let attributes = BlobAttributes {
+        version,
+        metadata: get_metadata(request),
+        ..Default::default()
+    };
+    storage.set_blob_attributes(container, blob, attributes.clone())?;
+
+    Ok(with_etag(HttpResponse::new(201), &attributes))
+}
+
+fn put_page(
+    storage: &mut dyn EmulatorStorage,
+    container: &str,
+    blob: &str,
+    request: &HttpRequest,
+) -> Result<HttpResponse, EmulatorError> {
+    let (start, end) = get_range(request)?.ok_or_else(EmulatorError::missing_required_header)?;
+
+    if start % BLOB_PAGE_SIZE != 0 || (end + 1) % BLOB_PAGE_SIZE != 0 {
+        return Err(EmulatorError::invalid_page_range());
+    }
+
+    let len = end + 1 - start;
+
+    if len > MAX_PUT_PAGE_SIZE {
+        return Err(EmulatorError::request_body_too_large());
+    }
+
+    let blob_size = storage.get_blob_size(container, blob)?;
+    let mut attributes = storage.get_blob_attributes(container, blob)?;
+
+    check_if_match(request, &attributes)?;
+    check_sequence_number_conditions(request, &attributes)?;
+
+    if end >= blob_size {
+        return Err(EmulatorError::invalid_page_range());
+    }
+
+    match request.get_header("x-ms-page-write") {
+        Some("update") => {
+            if request.body.len() != len {
+                return Err(EmulatorError::invalid_header_value());
+            }
+
+            storage.write(container, blob, start, &request.body)?;
+            attributes.add_page_range(start / BLOB_PAGE_SIZE..(end + 1) / BLOB_PAGE_SIZE);
+        }
+        Some("clear") => {
+            storage.write(container, blob, start, &vec![0u8; len])?;
+            attributes.remove_page_range(start / BLOB_PAGE_SIZE..(end + 1) / BLOB_PAGE_SIZE);
+        }
+        Some(_) => return Err(EmulatorError::invalid_header_value()),
+        None => return Err(EmulatorError::missing_required_header()),
+    }
+
+    attributes.version += 1;
+    storage.set_blob_attributes(container, blob, attributes.clone())?;
+
+    Ok(with_sequence_number(
+        with_etag(HttpResponse::new(201), &attributes),
+        &attributes,
+    ))
+}
+
+fn set_blob_properties(
+    storage: &mut dyn EmulatorStorage,
+    container: &str,
+    blob: &str,
+    request: &HttpRequest,
+) -> Result<HttpResponse, EmulatorError> {
+    storage.get_blob_size(container, blob)?;
+    let mut attributes = storage.get_blob_attributes(container, blob)?;
+
+    check_if_match(request, &attributes)?;
+
+    let blob_size = get_blob_content_length(request)?;
+    let sequence_number = get_sequence_number_after_action(request, &attributes)?;
+
+    if let Some(blob_size) = blob_size {
+        storage.resize_blob(container, blob, blob_size)?;
+        attributes.remove_page_range(blob_size / BLOB_PAGE_SIZE..usize::MAX);
+    }
+
+    if let Some(sequence_number) = sequence_number {
+        attributes.sequence_number = sequence_number;
+    }
+
+    attributes.version += 1;
+    storage.set_blob_attributes(container, blob, attributes.clone())?;
+
+    Ok(with_sequence_number(
+        with_etag(HttpResponse::new(200), &attributes),
+        &attributes,
+    ))
+}
+
+fn set_blob_metadata(
+    storage: &mut dyn EmulatorStorage,
+    container: &str,
+    blob: &str,
+    request: &HttpRequest,
+) -> Result<HttpResponse, EmulatorError> {
+    storage.get_blob_size(container, blob)?;
+    let mut attributes = storage.get_blob_attributes(container, blob)?;
+
+    check_if_match(request, &attributes)?;
+
+    attributes.metadata = get_metadata(request);
+    attributes.version += 1;
+    storage.set_blob_attributes(container, blob, attributes.clone())?;
+
+    Ok(with_etag(HttpResponse::new(200), &attributes))
+}
+
+// All ranges are returned in a single segment
+fn get_page_ranges(
+    storage: &mut dyn EmulatorStorage,
+    container: &str,
+    blob: &str,
+    request: &HttpRequest,
+) -> Result<HttpResponse, EmulatorError> {
+    let blob_size = storage.get_blob_size(container, blob)?;
+    let attributes = storage.get_blob_attributes(container, blob)?;
+
+    check_if_match(request, &attributes)?;
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?><PageList>");
+
+    for range in &attributes.page_ranges {
+        xml.push_str(&format!(
+            "<PageRange><Start>{}</Start><End>{}</End></PageRange>",
+            range.start * BLOB_PAGE_SIZE,
+            range.end * BLOB_PAGE_SIZE - 1
+        ));
+    }
+
+    xml.push_str("<NextMarker /></PageList>");
+
+    Ok(with_etag(xml_response(xml), &attributes)
+        .with_header("x-ms-blob-content-length", blob_size.to_string()))
+}
+
+fn get_blob(
+    storage: &mut dyn EmulatorStorage,
+    container: &str,
+    blob: &str,
+    request: &HttpRequest,
+) -> Result<HttpResponse, EmulatorError> {
+    let blob_size = storage.get_blob_size(container, blob)?;
+    let attributes = storage.get_blob_attributes(container, blob)?;
+
+    check_if_match(request, &attributes)?;
+
+    let (start, end) = match get_range(request)? {
+        Some(range) => range,
+        None => {
+            let payload = storage.read(container, blob, 0, blob_size)?;
+            return Ok(
+                with_blob_headers(HttpResponse::new(200), blob_size, &attributes)
+                    .with_body(payload),
+            );
+        }
+    };
+
+    if start >= blob_size {
+        return Err(EmulatorError::invalid_range());
+    }
+
+    let end = end.min(blob_size - 1);
+    let payload = storage.read(container, blob, start, end + 1 - start)?;
+
+    Ok(
+        with_blob_headers(HttpResponse::new(206), blob_size, &attributes)
+            .with_header(
+                "Content-Range",
+                format!("bytes {}-{}/{}", start, end, blob_size),
+            )
+            .with_body(payload),
+    )
+}
+
+fn with_blob_headers(
+    response: HttpResponse,
+    blob_size: usize,
+    attributes: &BlobAttributes,
+) -> HttpResponse {
+    let response = response
+        .with_header("x-ms-blob-type", "PageBlob".to_string())
+        .with_header("x-ms-blob-content-length", blob_size.to_string())
+        .with_header("Content-Type", "application/octet-stream".to_string());
+
+    with_metadata(
+        with_sequence_number(with_etag(response, attributes), attributes),
+        attributes,
+    )
+}
+
+fn with_metadata(mut response: HttpResponse, attributes: &BlobAttributes) -> HttpResponse {
+    for (name, value) in &attributes.metadata {
+        response = response.with_header(
+            &format!("{}{}", METADATA_HEADER_PREFIX, name),
+            value.clone(),
+        );
+    }
+
+    response
+}
+
+fn get_metadata(request: &HttpRequest) -> BTreeMap<String, String> {
+    request
+        .headers
+        .iter()
+        .filter_map(|(name, value)| {
+            name.strip_prefix(METADATA_HEADER_PREFIX)
+                .map(|name| (name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn with_etag(response: HttpResponse, attributes: &BlobAttributes) -> HttpResponse {
+    response.with_header("ETag", get_etag(attributes))
+}
+
+fn with_sequence_number(response: HttpResponse, attributes: &BlobAttributes) -> HttpResponse {
+    response.with_header(
+        "x-ms-blob-sequence-number",
+        attributes.sequence_number.to_string(),
+    )
+}
+
+fn get_etag(attributes: &BlobAttributes) -> String {
+    format!("\"0x{:X}\"", attributes.version)
+}
+
+fn check_if_match(request: &HttpRequest, attributes: &BlobAttributes) -> Result<(), EmulatorError> {
+    match request.get_header("if-match") {
+        Some("*") | None => Ok(()),
+        Some(etag) if etag == get_etag(attributes) => Ok(()),
+        Some(_) => Err(EmulatorError::condition_not_met()),
+    }
+}
+
+fn check_sequence_number_conditions(
+    request: &HttpRequest,
+    attributes: &BlobAttributes,
+) -> Result<(), EmulatorError> {
+    let conditions: [(&str, fn(u64, u64) -> bool); 3] = [
+        ("x-ms-if-sequence-number-le", |current, value| {
+            current <= value
+        }),
+        ("x-ms-if-sequence-number-lt", |current, value| {
+            current < value
+        }),
+        ("x-ms-if-sequence-number-eq", |current, value| {
+            current == value
+        }),
+    ];
+
+    for (header, is_met) in conditions {
+        if let Some(value) = get_sequence_number_header(request, header)? {
+            if !is_met(attributes.sequence_number, value) {
+                return Err(EmulatorError::sequence_number_condition_not_met());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn get_sequence_number_after_action(
+    request: &HttpRequest,
+    attributes: &BlobAttributes,
+) -> Result<Option<u64>, EmulatorError> {
+    let value = get_sequence_number_header(request, "x-ms-blob-sequence-number")?;
+
+    let result = match (request.get_header("x-ms-sequence-number-action"), value) {
+        (None, _) => return Ok(None),
+        (Some("update"), Some(value)) => value,
+        (Some("max"), Some(value)) => attributes.sequence_number.max(value),
+        (Some("increment"), None) => attributes.sequence_number + 1,
+        (Some("update" | "max"), None) => return Err(EmulatorError::missing_required_header()),
+        _ => return Err(EmulatorError::invalid_header_value()),
+    };
+
+    if result > MAX_SEQUENCE_NUMBER {
+        return Err(EmulatorError::invalid_header_value());
+    }
+
+    Ok(Some(result))
+}
+
+fn get_sequence_number_header(
+    request: &HttpRequest,
+    header: &str,
+) -> Result<Option<u64>, EmulatorError> {
+    match request.get_header(header) {
+        Some(value) => match value.parse::<u64>() {
+            Ok(value) if value <= MAX_SEQUENCE_NUMBER => Ok(Some(value)),
+            _ => Err(EmulatorError::invalid_header_value()),
+        },
+        None => Ok(None),
+    }
+}
+
+fn get_blob_content_length(request: &HttpRequest) -> Result<Option<usize>, EmulatorError> {
+    let blob_size = match request.get_header("x-ms-blob-content-length") {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| EmulatorError::invalid_header_value())?,
+        None => return Ok(None),
+    };
+
+    if blob_size % BLOB_PAGE_SIZE != 0 || blob_size as u64 > MAX_BLOB_SIZE {
+        return Err(EmulatorError::invalid_header_value());
+    }
+
+    Ok(Some(blob_size))
+}
+
+// Parses "bytes=start-end". The end is inclusive.
+fn get_range(request: &HttpRequest) -> Result<Option<(usize, usize)>, EmulatorError> {
+    let value = match request
+        .get_header("x-ms-range")
+        .or_else(|| request.get_header("range"))
+    {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    let (start, end) = value
+        .strip_prefix("bytes=")
+        .and_then(|range| range.split_once('-'))
+        .ok_or_else(EmulatorError::invalid_header_value)?;
+
+    let start = start
+        .parse::<usize>()
+        .map_err(|_| EmulatorError::invalid_header_value())?;
+    let end = end
+        .parse::<usize>()
+        .map_err(|_| EmulatorError::invalid_header_value())?;
+
+    if end < start {
+        return Err(EmulatorError::invalid_header_value());
+    }
+
+    Ok(Some((start, end)))
+}
+
+fn xml_response(xml: String) -> HttpResponse {
+    HttpResponse::new(200)
+        .with_header("Content-Type", "application/xml".to_string())
+        .with_body(xml.into_bytes())
+}
+
+fn escape_xml(src: &str) -> String {
+    src.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use my_azure_storage_sdk::AzureStorageConnection;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    fn create_request(
+        method: &str,
+        path: &str,
+        query: &[(&str, &str)],
+        headers: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> HttpRequest {
+        HttpRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: query
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<BTreeMap<_, _>>(),
+            body,
+        }
+    }
+
+    #[test]
+    fn test_page_blob_round_trip() {
+        let mut storage = MemoryStorage::new();
+        let blob_path = "/devstoreaccount1/container/tenant/1";
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "PUT",
+                blob_path,
+                &[],
+                &[
+                    ("x-ms-blob-type", "PageBlob"),
+                    ("x-ms-blob-content-length", "1024"),
+                ],
+                vec![],
+            ),
+        );
+        assert_eq!(404, response.status);
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "PUT",
+                "/devstoreaccount1/container",
+                &[("restype", "container")],
+                &[],
+                vec![],
+            ),
+        );
+        assert_eq!(201, response.status);
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "PUT",
+                blob_path,
+                &[],
+                &[
+                    ("x-ms-blob-type", "PageBlob"),
+                    ("x-ms-blob-content-length", "1024"),
+                ],
+                vec![],
+            ),
+        );
+        assert_eq!(201, response.status);
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "PUT",
+                blob_path,
+                &[("comp", "page")],
+                &[
+                    ("x-ms-page-write", "update"),
+                    ("x-ms-range", "bytes=512-1023"),
+                ],
+                vec![7u8; 512],
+            ),
+        );
+        assert_eq!(201, response.status);
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "PUT",
+                blob_path,
+                &[("comp", "page")],
+                &[
+                    ("x-ms-page-write", "update"),
+                    ("x-ms-range", "bytes=1024-1535"),
+                ],
+                vec![7u8; 512],
+            ),
+        );
+        assert_eq!(416, response.status);
+        assert!(response.headers.contains(&(
+            "x-ms-error-code".to_string(),
+            "InvalidPageRange".to_string()
+        )));
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "PUT",
+                blob_path,
+                &[("comp", "properties")],
+                &[("x-ms-blob-content-length", "2048")],
+                vec![],
+            ),
+        );
+        assert_eq!(200, response.status);
+
+        let response = handle_request(
+            &mut storage,
+            &create_request("HEAD", blob_path, &[], &[], vec![]),
+        );
+        assert_eq!(200, response.status);
+        assert_eq!(Some(2048), response.content_length);
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "GET",
+                blob_path,
+                &[],
+                &[("x-ms-range", "bytes=512-1535")],
+                vec![],
+            ),
+        );
+        assert_eq!(206, response.status);
+        assert_eq!(vec![7u8; 512], response.body[..512]);
+        assert_eq!(vec![0u8; 512], response.body[512..]);
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "GET",
+                "/devstoreaccount1/container",
+                &[("restype", "container"), ("comp", "list")],
+                &[],
+                vec![],
+            ),
+        );
+        let xml = String::from_utf8(response.body).unwrap();
+        assert!(xml.contains("<Name>tenant/1</Name>"));
+        assert!(xml.contains("<Content-Length>2048</Content-Length>"));
+
+        let response = handle_request(
+            &mut storage,
+            &create_request("DELETE", blob_path, &[], &[], vec![]),
+        );
+        assert_eq!(202, response.status);
+
+        let response = handle_request(
+            &mut storage,
+            &create_request("GET", blob_path, &[], &[], vec![]),
+        );
+        assert_eq!(404, response.status);
+    }
+
+    #[test]
+    fn test_sequence_number_conditions() {
+        let mut storage = MemoryStorage::new();
+        let blob_path = "/devstoreaccount1/container/blob";
+
+        storage.create_container("container").unwrap();
+        storage.create_blob("container", "blob", 1024).unwrap();
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "PUT",
+                blob_path,
+                &[("comp", "properties")],
+                &[
+                    ("x-ms-sequence-number-action", "update"),
+                    ("x-ms-blob-sequence-number", "5"),
+                ],
+                vec![],
+            ),
+        );
+        assert_eq!(200, response.status);
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "PUT",
+                blob_path,
+                &[("comp", "properties")],
+                &[("x-ms-sequence-number-action", "increment")],
+                vec![],
+            ),
+        );
+        assert!(response
+            .headers
+            .contains(&("x-ms-blob-sequence-number".to_string(), "6".to_string())));
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "PUT",
+                blob_path,
+                &[("comp", "page")],
+                &[
+                    ("x-ms-page-write", "update"),
+                    ("x-ms-range", "bytes=0-511"),
+                    ("x-ms-if-sequence-number-lt", "6"),
+                ],
+                vec![1u8; 512],
+            ),
+        );
+        assert_eq!(412, response.status);
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "PUT",
+                blob_path,
+                &[("comp", "page")],
+                &[
+                    ("x-ms-page-write", "update"),
+                    ("x-ms-range", "bytes=0-511"),
+                    ("x-ms-if-sequence-number-eq", "6"),
+                ],
+                vec![1u8; 512],
+            ),
+        );
+        assert_eq!(201, response.status);
+
+        let response = handle_request(
+            &mut storage,
+            &create_request("HEAD", blob_path, &[], &[], vec![]),
+        );
+        let (_, etag) = response
+            .headers
+            .iter()
+            .find(|(name, _)| name == "ETag")
+            .unwrap()
+            .clone();
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "PUT",
+                blob_path,
+                &[("comp", "properties")],
+                &[("x-ms-blob-content-length", "2048"), ("if-match", &etag)],
+                vec![],
+            ),
+        );
+        assert_eq!(200, response.status);
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "PUT",
+                blob_path,
+                &[("comp", "properties")],
+                &[("x-ms-blob-content-length", "4096"), ("if-match", &etag)],
+                vec![],
+            ),
+        );
+        assert_eq!(412, response.status);
+        assert_eq!(2048, storage.get_blob_size("container", "blob").unwrap());
+    }
+
+    #[test]
+    fn test_list_blobs_with_marker() {
+        let mut storage = MemoryStorage::new();
+        storage.create_container("container").unwrap();
+
+        for blob in ["a", "b", "c"] {
+            storage.create_blob("container", blob, 512).unwrap();
+        }
+
+        let list = |storage: &mut MemoryStorage, marker: &str| {
+            let response = handle_request(
+                storage,
+                &create_request(
+                    "GET",
+                    "/devstoreaccount1/container",
+                    &[
+                        ("restype", "container"),
+                        ("comp", "list"),
+                        ("maxresults", "2"),
+                        ("marker", marker),
+                    ],
+                    &[],
+                    vec![],
+                ),
+            );
+            String::from_utf8(response.body).unwrap()
+        };
+
+        let xml = list(&mut storage, "");
+        assert!(xml.contains("<Name>a</Name>") && xml.contains("<Name>b</Name>"));
+        assert!(xml.contains("<NextMarker>b</NextMarker>"));
+
+        let xml = list(&mut storage, "b");
+        assert!(!xml.contains("<Name>b</Name>") && xml.contains("<Name>c</Name>"));
+        assert!(xml.contains("<NextMarker />"));
+    }
+
+    #[tokio::test]
+    async fn test_serves_http() {
+        let addr = PageBlobEmulator::new_in_memory()
+            .start("127.0.0.1:0")
+            .await
+            .unwrap();
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"PUT /devstoreaccount1/container?restype=container HTTP/1.1\r\nConnection: close\r\nContent-Length: 0\r\n\r\n")
+            .await
+            .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 201 Created\r\n"));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(format!("PUT /devstoreaccount1/container/blob?comp=page HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_REQUEST_BODY_SIZE + 1).as_bytes())
+            .await
+            .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 413 Request Entity Too Large\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_conformance_against_emulator() {
+        let addr = PageBlobEmulator::new_in_memory()
+            .start("127.0.0.1:0")
+            .await
+            .unwrap();
+
+        let conn_string = get_emulator_connection_string(addr);
+
+        crate::run_conformance_suite(|| async {
+            crate::MyAzurePageBlob::new(
+                AzureStorageConnection::from_conn_string(&conn_string),
+                "conformance".to_string(),
+                "blob".to_string(),
+            )
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_etag_validated_pages_available_cache() {
+        use crate::MyPageBlob;
+
+        let addr = PageBlobEmulator::new_in_memory()
+            .start("127.0.0.1:0")
+            .await
+            .unwrap();
+
+        let connection = Arc::new(AzureStorageConnection::from_conn_string(
+            &get_emulator_connection_string(addr),
+        ));
+
+        let mut page_blob = crate::MyAzurePageBlob::builder(
+            connection.clone(),
+            "container".to_string(),
+            "blob".to_string(),
+        )
+        .with_auto_create_container(true)
+        .with_pages_available_cache(crate::PagesAvailableCachePolicy::ETagValidated)
+        .build();
+
+        let mut other_writer = crate::MyAzurePageBlob::new_with_shared_connection(
+            connection,
+            "container".to_string(),
+            "blob".to_string(),
+        );
+
+        page_blob.create(2).await.unwrap();
+
+        assert_eq!(2, page_blob.get_available_pages_amount().await.unwrap());
+        assert_eq!(2, page_blob.get_available_pages_amount().await.unwrap());
+
+        other_writer.resize(5).await.unwrap();
+
+        assert_eq!(5, page_blob.get_available_pages_amount().await.unwrap());
+    }
+
+    #[test]
+    fn test_head_if_none_match() {
+        let mut storage = MemoryStorage::new();
+        storage.create_container("container").unwrap();
+        storage.create_blob("container", "blob", 1024).unwrap();
+
+        let etag = get_etag(&storage.get_blob_attributes("container", "blob").unwrap());
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "HEAD",
+                "/container/blob",
+                &[],
+                &[("if-none-match", &etag)],
+                vec![],
+            ),
+        );
+        assert_eq!(304, response.status);
+
+        let response = handle_request(
+            &mut storage,
+            &create_request(
+                "HEAD",
+                "/container/blob",
+                &[],
+                &[("if-none-match", "\"0xFFFF\"")],
+                vec![],
+            ),
+        );
+        assert_eq!(200, response.status);
+    }
+
+    #[tokio::test]
+    async fn test_container_against_emulator() {
+        use crate::{MyPageBlob, PageBlobContainer};
+
+        let addr = PageBlobEmulator::new_in_memory()
+            .start("127.0.0.1:0")
+            .await
+            .unwrap();
+
+        let container = crate::MyAzurePageBlobContainer::new(
+            Arc::new(AzureStorageConnection::from_conn_string(
+                &get_emulator_connection_string(addr),
+            )),
+            "container".to_string(),
+        );
+
+        assert!(!container.exists().await.unwrap());
+
+        container.create_if_not_exists().await.unwrap();
+        assert!(container.exists().await.unwrap());
+
+        container.open_blob("blob").create(2).await.unwrap();
+
+        let segment = container.list_page_blobs_segment(None, None).await.unwrap();
+
+        assert_eq!(
+            vec![crate::PageBlobInfo {
+                name: "blob".to_string(),
+                blob_size: 2 * BLOB_PAGE_SIZE
+            }],
+            segment.blobs
+        );
+        assert_eq!(None, segment.continuation_token);
+    }
+}
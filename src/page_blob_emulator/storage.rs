@@ -0,0 +1,694 @@
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    ops::Range,
+    path::PathBuf,
+};
+
+use my_azure_storage_sdk::page_blob::consts::BLOB_PAGE_SIZE;
+
+use super::EmulatorError;
+
+// Kept next to the blob content. Version changes on every write and is returned as the ETag
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlobAttributes {
+    pub sequence_number: u64,
+    pub version: u64,
+    // Names are lowercased HTTP header names, values can not contain line breaks
+    pub metadata: BTreeMap<String, String>,
+    // Written pages. Ordered, adjacent ranges are merged
+    pub page_ranges: Vec<Range<usize>>,
+}
+
+impl BlobAttributes {
+    pub fn add_page_range(&mut self, range: Range<usize>) {
+        let mut merged = range;
+        let mut result = Vec::with_capacity(self.page_ranges.len() + 1);
+
+        for existing in self.page_ranges.drain(..) {
+            if existing.end < merged.start || existing.start > merged.end {
+                result.push(existing);
+            } else {
+                merged = existing.start.min(merged.start)..existing.end.max(merged.end);
+            }
+        }
+
+        result.push(merged);
+        result.sort_by_key(|range| range.start);
+        self.page_ranges = result;
+    }
+
+    pub fn remove_page_range(&mut self, range: Range<usize>) {
+        let mut result = Vec::with_capacity(self.page_ranges.len() + 1);
+
+        for existing in self.page_ranges.drain(..) {
+            if existing.start < range.start {
+                result.push(existing.start..existing.end.min(range.start));
+            }
+
+            if existing.end > range.end {
+                result.push(existing.start.max(range.end)..existing.end);
+            }
+        }
+
+        self.page_ranges = result;
+    }
+
+    fn serialize(&self) -> String {
+        let mut result = format!(
+            "sequence_number={}\nversion={}\n",
+            self.sequence_number, self.version
+        );
+
+        for range in &self.page_ranges {
+            result.push_str(&format!("page_range={}..{}\n", range.start, range.end));
+        }
+
+        for (name, value) in &self.metadata {
+            result.push_str(&format!("{}{}={}\n", METADATA_PREFIX, name, value));
+        }
+
+        result
+    }
+
+    fn deserialize(src: &str) -> Self {
+        let mut result = Self::default();
+
+        for (name, value) in src.lines().filter_map(|line| line.split_once('=')) {
+            match name {
+                "sequence_number" => result.sequence_number = value.parse().unwrap_or(0),
+                "version" => result.version = value.parse().unwrap_or(0),
+                "page_range" => {
+                    if let Some((start, end)) = value.split_once("..") {
+                        if let (Ok(start), Ok(end)) = (start.parse(), end.parse()) {
+                            result.page_ranges.push(start..end);
+                        }
+                    }
+                }
+                _ => {
+                    if let Some(name) = name.strip_prefix(METADATA_PREFIX) {
+                        result.metadata.insert(name.to_string(), value.to_string());
+                    }
+                }
+            }
+        }
+
+        result
+    }
+}
+
+const METADATA_PREFIX: &str = "metadata.";
+
+pub trait EmulatorStorage: Send {
+    fn create_container(&mut self, container: &str) -> Result<(), EmulatorError>;
+    fn delete_container(&mut self, container: &str) -> Result<(), EmulatorError>;
+    fn container_exists(&self, container: &str) -> Result<bool, EmulatorError>;
+    fn list_containers(&self) -> Result<Vec<String>, EmulatorError>;
+    fn list_blobs(&self, container: &str) -> Result<Vec<(String, usize)>, EmulatorError>;
+
+    // Creates the blob filled with zeros or overwrites the existing one
+    fn create_blob(
+        &mut self,
+        container: &str,
+        blob: &str,
+        size: usize,
+    ) -> Result<(), EmulatorError>;
+    fn get_blob_size(&self, container: &str, blob: &str) -> Result<usize, EmulatorError>;
+    fn resize_blob(
+        &mut self,
+        container: &str,
+        blob: &str,
+        size: usize,
+    ) -> Result<(), EmulatorError>;
+    fn delete_blob(&mut self, container: &str, blob: &str) -> Result<(), EmulatorError>;
+
+    fn get_blob_attributes(
+        &self,
+        container: &str,
+        blob: &str,
+    ) -> Result<BlobAttributes, EmulatorError>;
+    fn set_blob_attributes(
+        &mut self,
+        container: &str,
+        blob: &str,
+        attributes: BlobAttributes,
+    ) -> Result<(), EmulatorError>;
+
+    // Offsets and lengths are validated against the blob size by the caller
+    fn write(
+        &mut self,
+        container: &str,
+        blob: &str,
+        offset: usize,
+        payload: &[u8],
+    ) -> Result<(), EmulatorError>;
+    fn read(
+        &mut self,
+        container: &str,
+        blob: &str,
+        offset: usize,
+        len: usize,
+    ) -> Result<Vec<u8>, EmulatorError>;
+}
+
+// Pages are allocated on the first write, so huge blobs can be created without allocating their size
+struct MemoryBlob {
+    size: usize,
+    pages: BTreeMap<usize, [u8; BLOB_PAGE_SIZE]>,
+    attributes: BlobAttributes,
+}
+
+pub struct MemoryStorage {
+    containers: BTreeMap<String, BTreeMap<String, MemoryBlob>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self {
+            containers: BTreeMap::new(),
+        }
+    }
+
+    fn get_container_mut(
+        &mut self,
+        container: &str,
+    ) -> Result<&mut BTreeMap<String, MemoryBlob>, EmulatorError> {
+        self.containers
+            .get_mut(container)
+            .ok_or_else(EmulatorError::container_not_found)
+    }
+
+    fn get_blob_mut(
+        &mut self,
+        container: &str,
+        blob: &str,
+    ) -> Result<&mut MemoryBlob, EmulatorError> {
+        self.get_container_mut(container)?
+            .get_mut(blob)
+            .ok_or_else(EmulatorError::blob_not_found)
+    }
+}
+
+impl EmulatorStorage for MemoryStorage {
+    fn create_container(&mut self, container: &str) -> Result<(), EmulatorError> {
+        if self.containers.contains_key(container) {
+            return Err(EmulatorError::container_already_exists());
+        }
+
+        self.containers
+            .insert(container.to_string(), BTreeMap::new());
+        Ok(())
+    }
+
+    fn delete_container(&mut self, container: &str) -> Result<(), EmulatorError> {
+        self.containers
+            .remove(container)
+            .ok_or_else(EmulatorError::container_not_found)?;
+        Ok(())
+    }
+
+    fn container_exists(&self, container: &str) -> Result<bool, EmulatorError> {
+        Ok(self.containers.contains_key(container))
+    }
+
+    fn list_containers(&self) -> Result<Vec<String>, EmulatorError> {
+        Ok(self.containers.keys().cloned().collect())
+    }
+
+    fn list_blobs(&self, container: &str) -> Result<Vec<(String, usize)>, EmulatorError> {
+        let blobs = self
+            .containers
+            .get(container)
+            .ok_or_else(EmulatorError::container_not_found)?;
+
+        Ok(blobs
+            .iter()
+            .map(|(name, blob)| (name.to_string(), blob.size))
+            .collect())
+    }
+
+    fn create_blob(
+        &mut self,
+        container: &str,
+        blob: &str,
+        size: usize,
+    ) -> Result<(), EmulatorError> {
+        self.get_container_mut(container)?.insert(
+            blob.to_string(),
+            MemoryBlob {
+                size,
+                pages: BTreeMap::new(),
+                attributes: BlobAttributes::default(),
+            },
+        );
+        Ok(())
+    }
+
+    fn get_blob_size(&self, container: &str, blob: &str) -> Result<usize, EmulatorError> {
+        let blobs = self
+            .containers
+            .get(container)
+            .ok_or_else(EmulatorError::container_not_found)?;
+
+        let blob = blobs.get(blob).ok_or_else(EmulatorError::blob_not_found)?;
+        Ok(blob.size)
+    }
+
+    fn resize_blob(
+        &mut self,
+        container: &str,
+        blob: &str,
+        size: usize,
+    ) -> Result<(), EmulatorError> {
+        let blob = self.get_blob_mut(container, blob)?;
+        blob.size = size;
+        blob.pages
+            .retain(|page_no, _| page_no * BLOB_PAGE_SIZE < size);
+        Ok(())
+    }
+
+    fn delete_blob(&mut self, container: &str, blob: &str) -> Result<(), EmulatorError> {
+        self.get_container_mut(container)?
+            .remove(blob)
+            .ok_or_else(EmulatorError::blob_not_found)?;
+        Ok(())
+    }
+
+    fn get_blob_attributes(
+        &self,
+        container: &str,
+        blob: &str,
+    ) -> Result<BlobAttributes, EmulatorError> {
+        let blobs = self
+            .containers
+            .get(container)
+            .ok_or_else(EmulatorError::container_not_found)?;
+
+        let blob = blobs.get(blob).ok_or_else(EmulatorError::blob_not_found)?;
+        Ok(blob.attributes.clone())
+    }
+
+    fn set_blob_attributes(
+        &mut self,
+        container: &str,
+        blob: &str,
+        attributes: BlobAttributes,
+    ) -> Result<(), EmulatorError> {
+        self.get_blob_mut(container, blob)?.attributes = attributes;
+        Ok(())
+    }
+
+    fn write(
+        &mut self,
+        container: &str,
+        blob: &str,
+        offset: usize,
+        payload: &[u8],
+    ) -> Result<(), EmulatorError> {
+        let blob = self.get_blob_mut(container, blob)?;
+
+        for (index, chunk) in payload.chunks(BLOB_PAGE_SIZE).enumerate() {
+            let page = blob
+                .pages
+                .entry(offset / BLOB_PAGE_SIZE + index)
+                .or_insert([0u8; BLOB_PAGE_SIZE]);
+            page[..chunk.len()].copy_from_slice(chunk);
+        }
+
+        Ok(())
+    }
+
+    fn read(
+        &mut self,
+        container: &str,
+        blob: &str,
+        offset: usize,
+        len: usize,
+    ) -> Result<Vec<u8>, EmulatorError> {
+        let blob = self.get_blob_mut(container, blob)?;
+        let mut result = vec![0u8; len];
+
+        for (page_no, page) in blob
+            .pages
+            .range(offset / BLOB_PAGE_SIZE..=(offset + len) / BLOB_PAGE_SIZE)
+        {
+            let page_start = page_no * BLOB_PAGE_SIZE;
+            let from = page_start.max(offset);
+            let to = (page_start + BLOB_PAGE_SIZE).min(offset + len);
+
+            if from < to {
+                result[from - offset..to - offset]
+                    .copy_from_slice(&page[from - page_start..to - page_start]);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+// Every container is a directory and every blob is a sparse file inside it.
+// Blob names are escaped, so names with '/' do not create nested directories.
+// Attributes are kept in a sidecar file, its suffix can not appear in an escaped name.
+const ATTRIBUTES_FILE_SUFFIX: &str = "%attributes";
+
+pub struct DiskStorage {
+    root: PathBuf,
+}
+
+impl DiskStorage {
+    pub fn new(root: PathBuf) -> Result<Self, EmulatorError> {
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    fn get_container_path(&self, container: &str) -> PathBuf {
+        self.root.join(escape_file_name(container))
+    }
+
+    fn get_blob_path(&self, container: &str, blob: &str) -> Result<PathBuf, EmulatorError> {
+        let container_path = self.get_container_path(container);
+
+        if !container_path.is_dir() {
+            return Err(EmulatorError::container_not_found());
+        }
+
+        Ok(container_path.join(escape_file_name(blob)))
+    }
+
+    fn get_attributes_path(&self, container: &str, blob: &str) -> Result<PathBuf, EmulatorError> {
+        let container_path = self.get_container_path(container);
+
+        if !container_path.is_dir() {
+            return Err(EmulatorError::container_not_found());
+        }
+
+        Ok(container_path.join(format!(
+            "{}{}",
+            escape_file_name(blob),
+            ATTRIBUTES_FILE_SUFFIX
+        )))
+    }
+
+    fn open_blob(&self, container: &str, blob: &str) -> Result<File, EmulatorError> {
+        let blob_path = self.get_blob_path(container, blob)?;
+
+        if !blob_path.is_file() {
+            return Err(EmulatorError::blob_not_found());
+        }
+
+        Ok(OpenOptions::new().read(true).write(true).open(blob_path)?)
+    }
+}
+
+impl EmulatorStorage for DiskStorage {
+    fn create_container(&mut self, container: &str) -> Result<(), EmulatorError> {
+        let container_path = self.get_container_path(container);
+
+        if container_path.is_dir() {
+            return Err(EmulatorError::container_already_exists());
+        }
+
+        std::fs::create_dir(container_path)?;
+        Ok(())
+    }
+
+    fn delete_container(&mut self, container: &str) -> Result<(), EmulatorError> {
+        let container_path = self.get_container_path(container);
+
+        if !container_path.is_dir() {
+            return Err(EmulatorError::container_not_found());
+        }
+
+        std::fs::remove_dir_all(container_path)?;
+        Ok(())
+    }
+
+    fn container_exists(&self, container: &str) -> Result<bool, EmulatorError> {
+        Ok(self.get_container_path(container).is_dir())
+    }
+
+    fn list_containers(&self) -> Result<Vec<String>, EmulatorError> {
+        let mut result = Vec::new();
+
+        for entry in std::fs::read_dir(&self.root)? {
+            let entry = entry?;
+
+            if entry.file_type()?.is_dir() {
+                result.push(unescape_file_name(&entry.file_name().to_string_lossy()));
+            }
+        }
+
+        result.sort();
+        Ok(result)
+    }
+
+    fn list_blobs(&self, container: &str) -> Result<Vec<(String, usize)>, EmulatorError> {
+        let container_path = self.get_container_path(container);
+
+        if !container_path.is_dir() {
+            return Err(EmulatorError::container_not_found());
+        }
+
+        let mut result = Vec::new();
+
+        for entry in std::fs::read_dir(container_path)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+
+            let file_name = entry.file_name().to_string_lossy().to_string();
+
+            if metadata.is_file() && !file_name.ends_with(ATTRIBUTES_FILE_SUFFIX) {
+                result.push((unescape_file_name(&file_name), metadata.len() as usize));
+            }
+        }
+
+        result.sort();
+        Ok(result)
+    }
+
+    fn create_blob(
+        &mut self,
+        container: &str,
+        blob: &str,
+        size: usize,
+    ) -> Result<(), EmulatorError> {
+        let file = File::create(self.get_blob_path(container, blob)?)?;
+        file.set_len(size as u64)?;
+
+        let attributes_path = self.get_attributes_path(container, blob)?;
+
+        if attributes_path.is_file() {
+            std::fs::remove_file(attributes_path)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_blob_size(&self, container: &str, blob: &str) -> Result<usize, EmulatorError> {
+        let file = self.open_blob(container, blob)?;
+        Ok(file.metadata()?.len() as usize)
+    }
+
+    fn resize_blob(
+        &mut self,
+        container: &str,
+        blob: &str,
+        size: usize,
+    ) -> Result<(), EmulatorError> {
+        let file = self.open_blob(container, blob)?;
+        file.set_len(size as u64)?;
+        Ok(())
+    }
+
+    fn delete_blob(&mut self, container: &str, blob: &str) -> Result<(), EmulatorError> {
+        let blob_path = self.get_blob_path(container, blob)?;
+
+        if !blob_path.is_file() {
+            return Err(EmulatorError::blob_not_found());
+        }
+
+        std::fs::remove_file(blob_path)?;
+
+        let attributes_path = self.get_attributes_path(container, blob)?;
+
+        if attributes_path.is_file() {
+            std::fs::remove_file(attributes_path)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_blob_attributes(
+        &self,
+        container: &str,
+        blob: &str,
+    ) -> Result<BlobAttributes, EmulatorError> {
+        self.open_blob(container, blob)?;
+
+        match std::fs::read_to_string(self.get_attributes_path(container, blob)?) {
+            Ok(src) => Ok(BlobAttributes::deserialize(&src)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(BlobAttributes::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn set_blob_attributes(
+        &mut self,
+        container: &str,
+        blob: &str,
+        attributes: BlobAttributes,
+    ) -> Result<(), EmulatorError> {
+        self.open_blob(container, blob)?;
+        std::fs::write(
+            self.get_attributes_path(container, blob)?,
+            attributes.serialize(),
+        )?;
+        Ok(())
+    }
+
+    fn write(
+        &mut self,
+        container: &str,
+        blob: &str,
+        offset: usize,
+        payload: &[u8],
+    ) -> Result<(), EmulatorError> {
+        let mut file = self.open_blob(container, blob)?;
+        file.seek(SeekFrom::Start(offset as u64))?;
+        file.write_all(payload)?;
+        Ok(())
+    }
+
+    fn read(
+        &mut self,
+        container: &str,
+        blob: &str,
+        offset: usize,
+        len: usize,
+    ) -> Result<Vec<u8>, EmulatorError> {
+        let mut file = self.open_blob(container, blob)?;
+        file.seek(SeekFrom::Start(offset as u64))?;
+
+        let mut result = vec![0u8; len];
+        file.read_exact(&mut result)?;
+        Ok(result)
+    }
+}
+
+fn escape_file_name(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+
+    for c in name.chars() {
+        match c {
+            '%' => result.push_str("%25"),
+            '/' => result.push_str("%2F"),
+            '\\' => result.push_str("%5C"),
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+fn unescape_file_name(name: &str) -> String {
+    name.replace("%2F", "/")
+        .replace("%5C", "\\")
+        .replace("%25", "%")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_storage_reads_sparse_pages() {
+        let mut storage = MemoryStorage::new();
+        storage.create_container("container").unwrap();
+        storage.create_blob("container", "blob", 2048).unwrap();
+
+        storage
+            .write("container", "blob", 512, &[1u8; 512])
+            .unwrap();
+
+        let result = storage.read("container", "blob", 256, 1024).unwrap();
+
+        assert_eq!(vec![0u8; 256], result[..256]);
+        assert_eq!(vec![1u8; 512], result[256..768]);
+        assert_eq!(vec![0u8; 256], result[768..]);
+
+        storage.resize_blob("container", "blob", 512).unwrap();
+        storage.resize_blob("container", "blob", 1024).unwrap();
+        assert_eq!(
+            vec![0u8; 512],
+            storage.read("container", "blob", 512, 512).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_disk_storage() {
+        let root = std::env::temp_dir().join(format!("page-blob-emulator-{}", std::process::id()));
+        let mut storage = DiskStorage::new(root.clone()).unwrap();
+
+        storage.create_container("container").unwrap();
+        storage.create_blob("container", "tenant/1", 1024).unwrap();
+        storage
+            .write("container", "tenant/1", 512, &[1u8; 512])
+            .unwrap();
+
+        let attributes = BlobAttributes {
+            sequence_number: 5,
+            version: 2,
+            metadata: BTreeMap::from([("logical_length".to_string(), "700".to_string())]),
+            page_ranges: vec![1..2, 4..6],
+        };
+
+        storage
+            .set_blob_attributes("container", "tenant/1", attributes.clone())
+            .unwrap();
+
+        assert_eq!(
+            vec![("tenant/1".to_string(), 1024)],
+            storage.list_blobs("container").unwrap()
+        );
+        assert_eq!(
+            attributes,
+            storage
+                .get_blob_attributes("container", "tenant/1")
+                .unwrap()
+        );
+        assert_eq!(
+            vec![1u8; 512],
+            storage.read("container", "tenant/1", 512, 512).unwrap()
+        );
+
+        storage.delete_container("container").unwrap();
+        assert!(!storage.container_exists("container").unwrap());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_page_ranges() {
+        let mut attributes = BlobAttributes::default();
+
+        attributes.add_page_range(4..6);
+        attributes.add_page_range(0..1);
+        attributes.add_page_range(6..8);
+        attributes.add_page_range(1..2);
+        assert_eq!(vec![0..2, 4..8], attributes.page_ranges);
+
+        attributes.add_page_range(1..5);
+        assert_eq!(vec![0..8], attributes.page_ranges);
+
+        attributes.remove_page_range(2..3);
+        attributes.remove_page_range(7..usize::MAX);
+        assert_eq!(vec![0..2, 3..7], attributes.page_ranges);
+    }
+
+    #[test]
+    fn test_file_names_are_escaped() {
+        assert_eq!("tenant%2F1%25", escape_file_name("tenant/1%"));
+        assert_eq!("tenant/1%", unescape_file_name("tenant%2F1%25"));
+    }
+}
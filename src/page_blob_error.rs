@@ -0,0 +1,146 @@
+use std::{fmt, ops::Range, time::Duration};
+
+use my_azure_storage_sdk::AzureStorageError;
+
+#[derive(Debug)]
+pub enum PageBlobError {
+    OutOfRange {
+        requested: usize,
+        available: usize,
+    },
+    NotAligned {
+        len: usize,
+    },
+    BlobTooLarge {
+        requested_size: u64,
+        max_size: u64,
+    },
+    Corrupted {
+        msg: String,
+    },
+    Timeout {
+        operation: String,
+        timeout: Duration,
+        bytes_written: Option<usize>,
+    },
+    PreconditionFailed {
+        msg: String,
+    },
+    SequenceNumberTooLarge {
+        value: u64,
+        max_value: u64,
+    },
+    NotSupported {
+        msg: String,
+    },
+    OverlappingRanges {
+        page_no: usize,
+    },
+    // Chunked write failed. Pages after the committed prefix may be written as well,
+    // as chunks are sent concurrently
+    PartialWrite {
+        pages_written: usize,
+        failed_pages: Range<usize>,
+        err: Box<PageBlobError>,
+    },
+    // Blob REST response with a 5xx status
+    ServerError {
+        status: u16,
+        code: String,
+    },
+    Io(std::io::Error),
+    Azure(AzureStorageError),
+}
+
+impl PageBlobError {
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            PageBlobError::Timeout { .. } => true,
+            PageBlobError::PartialWrite { err, .. } => err.is_timeout(),
+            _ => false,
+        }
+    }
+}
+
+impl From<AzureStorageError> for PageBlobError {
+    fn from(src: AzureStorageError) -> Self {
+        Self::Azure(src)
+    }
+}
+
+impl From<std::io::Error> for PageBlobError {
+    fn from(src: std::io::Error) -> Self {
+        Self::Io(src)
+    }
+}
+
+impl fmt::Display for PageBlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PageBlobError::OutOfRange {
+                requested,
+                available,
+            } => write!(
+                f,
+                "Requires blob with the pages amount: {}. Available pages amount is: {}",
+                requested, available
+            ),
+            PageBlobError::NotAligned { len } => {
+                write!(f, "Length {} is not aligned to the page size", len)
+            }
+            PageBlobError::BlobTooLarge {
+                requested_size,
+                max_size,
+            } => write!(
+                f,
+                "Blob size {} exceeds max allowed blob size {}",
+                requested_size, max_size
+            ),
+            PageBlobError::Corrupted { msg } => write!(f, "Blob is corrupted: {}", msg),
+            PageBlobError::Timeout {
+                operation,
+                timeout,
+                bytes_written,
+            } => match bytes_written {
+                Some(bytes_written) => write!(
+                    f,
+                    "Operation {} is not finished in {:?}. Bytes written: {}",
+                    operation, timeout, bytes_written
+                ),
+                None => write!(
+                    f,
+                    "Operation {} is not finished in {:?}",
+                    operation, timeout
+                ),
+            },
+            PageBlobError::PreconditionFailed { msg } => {
+                write!(f, "Precondition failed: {}", msg)
+            }
+            PageBlobError::SequenceNumberTooLarge { value, max_value } => write!(
+                f,
+                "Sequence number {} exceeds max allowed value {}",
+                value, max_value
+            ),
+            PageBlobError::NotSupported { msg } => write!(f, "Not supported: {}", msg),
+            PageBlobError::OverlappingRanges { page_no } => {
+                write!(f, "Page ranges overlap at page {}", page_no)
+            }
+            PageBlobError::PartialWrite {
+                pages_written,
+                failed_pages,
+                err,
+            } => write!(
+                f,
+                "Write of pages {:?} is failed. Pages written: {}. Err: {}",
+                failed_pages, pages_written, err
+            ),
+            PageBlobError::ServerError { status, code } => {
+                write!(f, "Server error with status {}: {}", status, code)
+            }
+            PageBlobError::Io(err) => write!(f, "IO error: {}", err),
+            PageBlobError::Azure(err) => write!(f, "Azure storage error: {:?}", err),
+        }
+    }
+}
+
+impl std::error::Error for PageBlobError {}
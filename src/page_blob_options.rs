@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use crate::PagesAvailableCachePolicy;
+
+#[derive(Debug, Clone)]
+pub struct PageBlobOptions {
+    pub max_pages_to_write: usize,
+    pub resize_pages_ratio: usize,
+    pub retries: usize,
+    pub retry_delay: Duration,
+    pub request_timeout: Option<Duration>,
+    pub operation_timeout: Option<Duration>,
+    pub max_concurrent_requests: usize,
+    pub auto_create_container: bool,
+    pub pages_available_cache: PagesAvailableCachePolicy,
+    // Ranges of a batch read separated by up to this amount of pages are read by a single request
+    pub max_read_gap_pages: usize,
+}
+
+impl Default for PageBlobOptions {
+    fn default() -> Self {
+        Self {
+            max_pages_to_write: 8192,
+            resize_pages_ratio: 8192,
+            retries: 0,
+            retry_delay: Duration::from_millis(100),
+            request_timeout: None,
+            operation_timeout: None,
+            max_concurrent_requests: 1,
+            auto_create_container: false,
+            pages_available_cache: PagesAvailableCachePolicy::RefreshOnRangeError,
+            max_read_gap_pages: 8,
+        }
+    }
+}
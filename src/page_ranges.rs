@@ -0,0 +1,308 @@
+use std::{ops::Range, sync::Arc};
+
+use bytes::{Bytes, BytesMut};
+use my_azure_storage_sdk::page_blob::consts::BLOB_PAGE_SIZE;
+
+use crate::{limits::get_end_page_no, sdk::ressize_payload_to_fullpage, PageBlobError};
+
+// Written bytes of the range. Ranges merged into a single write share the error of that write
+pub type PageRangeResult = Result<usize, Arc<PageBlobError>>;
+
+pub struct MergedPageRange {
+    pub start_page_no: usize,
+    pub payload: Bytes,
+    // Index of the source range and its size padded to full pages
+    pub ranges: Vec<(usize, usize)>,
+}
+
+impl MergedPageRange {
+    pub fn get_pages(&self) -> Range<usize> {
+        self.start_page_no..self.start_page_no + self.payload.len() / BLOB_PAGE_SIZE
+    }
+}
+
+pub fn get_pages_amount_needs(merged_ranges: &[MergedPageRange]) -> usize {
+    merged_ranges
+        .iter()
+        .map(|merged_range| merged_range.get_pages().end)
+        .max()
+        .unwrap_or(0)
+}
+
+struct PageRangesGroup {
+    start_page_no: usize,
+    payloads: Vec<Bytes>,
+    ranges: Vec<(usize, usize)>,
+}
+
+// Pads payloads to full pages and merges adjacent ranges. Empty payloads are skipped
+pub fn merge_page_ranges(
+    ranges: Vec<(usize, Bytes)>,
+) -> Result<Vec<MergedPageRange>, PageBlobError> {
+    let mut ranges: Vec<(usize, usize, Bytes)> = ranges
+        .into_iter()
+        .enumerate()
+        .filter(|(_, (_, payload))| !payload.is_empty())
+        .map(|(index, (start_page_no, payload))| {
+            (index, start_page_no, ressize_payload_to_fullpage(payload))
+        })
+        .collect();
+
+    ranges.sort_by_key(|(_, start_page_no, _)| *start_page_no);
+
+    let mut groups: Vec<PageRangesGroup> = Vec::new();
+    let mut end_page_no = 0;
+
+    for (index, start_page_no, payload) in ranges {
+        let pages_amount = payload.len() / BLOB_PAGE_SIZE;
+
+        match groups.last_mut() {
+            Some(_) if start_page_no < end_page_no => {
+                return Err(PageBlobError::OverlappingRanges {
+                    page_no: start_page_no,
+                });
+            }
+            Some(group) if start_page_no == end_page_no => {
+                group.ranges.push((index, payload.len()));
+                group.payloads.push(payload);
+            }
+            _ => {
+                groups.push(PageRangesGroup {
+                    start_page_no,
+                    ranges: vec![(index, payload.len())],
+                    payloads: vec![payload],
+                });
+            }
+        }
+
+        end_page_no = get_end_page_no(start_page_no, pages_amount)?;
+    }
+
+    let result = groups
+        .into_iter()
+        .map(|group| MergedPageRange {
+            start_page_no: group.start_page_no,
+            payload: concat_payloads(group.payloads),
+            ranges: group.ranges,
+        })
+        .collect();
+
+    Ok(result)
+}
+
+fn concat_payloads(mut payloads: Vec<Bytes>) -> Bytes {
+    if payloads.len() == 1 {
+        return payloads.remove(0);
+    }
+
+    let mut result = BytesMut::with_capacity(payloads.iter().map(|payload| payload.len()).sum());
+
+    for payload in payloads {
+        result.extend_from_slice(&payload);
+    }
+
+    return result.freeze();
+}
+
+// Ranges read by a single request
+pub struct CoalescedPageRange {
+    pub start_page_no: usize,
+    pub pages_amount: usize,
+    // Indexes of the requested ranges
+    pub ranges: Vec<usize>,
+}
+
+// Ranges which overlap or are separated by up to max_gap_pages are coalesced. Empty ranges are skipped
+pub fn coalesce_page_ranges(
+    ranges: &[(usize, usize)],
+    max_gap_pages: usize,
+) -> Result<Vec<CoalescedPageRange>, PageBlobError> {
+    let mut indexes: Vec<usize> = (0..ranges.len())
+        .filter(|index| ranges[*index].1 > 0)
+        .collect();
+
+    indexes.sort_by_key(|index| ranges[*index].0);
+
+    let mut result: Vec<CoalescedPageRange> = Vec::new();
+
+    for index in indexes {
+        let (start_page_no, pages_amount) = ranges[index];
+        let end_page_no = get_end_page_no(start_page_no, pages_amount)?;
+
+        if let Some(coalesced) = result.last_mut() {
+            let coalesced_end_page_no =
+                get_end_page_no(coalesced.start_page_no, coalesced.pages_amount)?;
+
+            if start_page_no <= coalesced_end_page_no.saturating_add(max_gap_pages) {
+                coalesced.pages_amount =
+                    end_page_no.max(coalesced_end_page_no) - coalesced.start_page_no;
+                coalesced.ranges.push(index);
+                continue;
+            }
+        }
+
+        result.push(CoalescedPageRange {
+            start_page_no,
+            pages_amount,
+            ranges: vec![index],
+        });
+    }
+
+    Ok(result)
+}
+
+// Splits the payloads of the coalesced reads back to the requested ranges without copying
+pub fn split_coalesced_payloads(
+    ranges: &[(usize, usize)],
+    coalesced_ranges: &[CoalescedPageRange],
+    payloads: Vec<Bytes>,
+) -> Result<Vec<Bytes>, PageBlobError> {
+    let mut result = vec![Bytes::new(); ranges.len()];
+
+    for (coalesced, payload) in coalesced_ranges.iter().zip(payloads) {
+        for index in &coalesced.ranges {
+            let (start_page_no, pages_amount) = ranges[*index];
+            let start_page_index = start_page_no - coalesced.start_page_no;
+            let end_page_index = get_end_page_no(start_page_index, pages_amount)?;
+            result[*index] =
+                payload.slice(start_page_index * BLOB_PAGE_SIZE..end_page_index * BLOB_PAGE_SIZE);
+        }
+    }
+
+    Ok(result)
+}
+
+// Spreads the results of the merged writes over the source ranges
+pub fn get_page_range_results(
+    ranges_amount: usize,
+    merged_ranges: &[MergedPageRange],
+    results: Vec<Result<(), PageBlobError>>,
+) -> Vec<PageRangeResult> {
+    let mut page_range_results: Vec<PageRangeResult> = vec![Ok(0); ranges_amount];
+
+    for (merged_range, result) in merged_ranges.iter().zip(results) {
+        let result = result.map_err(Arc::new);
+
+        for (index, size) in &merged_range.ranges {
+            page_range_results[*index] = match &result {
+                Ok(()) => Ok(*size),
+                Err(err) => Err(err.clone()),
+            };
+        }
+    }
+
+    page_range_results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_page_ranges() {
+        let merged = merge_page_ranges(vec![
+            (3, Bytes::from(vec![3u8; 10])),
+            (0, Bytes::from(vec![0u8; BLOB_PAGE_SIZE])),
+            (5, Bytes::new()),
+            (1, Bytes::from(vec![1u8; 2 * BLOB_PAGE_SIZE])),
+            (7, Bytes::from(vec![7u8; BLOB_PAGE_SIZE])),
+        ])
+        .unwrap();
+
+        assert_eq!(2, merged.len());
+
+        assert_eq!(0..4, merged[0].get_pages());
+        assert_eq!(
+            vec![
+                (1, BLOB_PAGE_SIZE),
+                (3, 2 * BLOB_PAGE_SIZE),
+                (0, BLOB_PAGE_SIZE)
+            ],
+            merged[0].ranges
+        );
+        assert_eq!(1, merged[0].payload[BLOB_PAGE_SIZE]);
+        assert_eq!(3, merged[0].payload[3 * BLOB_PAGE_SIZE]);
+        assert_eq!(0, merged[0].payload[3 * BLOB_PAGE_SIZE + 10]);
+
+        assert_eq!(7..8, merged[1].get_pages());
+
+        let results = get_page_range_results(
+            5,
+            &merged,
+            vec![
+                Ok(()),
+                Err(PageBlobError::OutOfRange {
+                    requested: 8,
+                    available: 7,
+                }),
+            ],
+        );
+
+        assert_eq!(BLOB_PAGE_SIZE, *results[0].as_ref().unwrap());
+        assert_eq!(0, *results[2].as_ref().unwrap());
+        assert!(results[4].is_err());
+    }
+
+    #[test]
+    fn test_coalesce_page_ranges() {
+        let ranges = [(10, 2), (0, 1), (3, 2), (4, 3), (20, 0), (30, 1)];
+
+        let coalesced = coalesce_page_ranges(&ranges, 2).unwrap();
+
+        assert_eq!(3, coalesced.len());
+        assert_eq!(
+            (0, 7),
+            (coalesced[0].start_page_no, coalesced[0].pages_amount)
+        );
+        assert_eq!(vec![1, 2, 3], coalesced[0].ranges);
+        assert_eq!(
+            (10, 2),
+            (coalesced[1].start_page_no, coalesced[1].pages_amount)
+        );
+        assert_eq!(
+            (30, 1),
+            (coalesced[2].start_page_no, coalesced[2].pages_amount)
+        );
+
+        let payloads = coalesced
+            .iter()
+            .map(|coalesced| {
+                (coalesced.start_page_no
+                    ..get_end_page_no(coalesced.start_page_no, coalesced.pages_amount).unwrap())
+                    .flat_map(|page_no| vec![page_no as u8; BLOB_PAGE_SIZE])
+                    .collect::<Vec<u8>>()
+                    .into()
+            })
+            .collect();
+
+        let result = split_coalesced_payloads(&ranges, &coalesced, payloads).unwrap();
+
+        assert_eq!(6, result.len());
+        assert_eq!(vec![10u8; BLOB_PAGE_SIZE], result[0][..BLOB_PAGE_SIZE]);
+        assert_eq!(2 * BLOB_PAGE_SIZE, result[0].len());
+        assert_eq!(vec![4u8; BLOB_PAGE_SIZE], result[3][..BLOB_PAGE_SIZE]);
+        assert_eq!(vec![6u8; BLOB_PAGE_SIZE], result[3][2 * BLOB_PAGE_SIZE..]);
+        assert!(result[4].is_empty());
+        assert_eq!(vec![30u8; BLOB_PAGE_SIZE], result[5]);
+    }
+
+    #[test]
+    fn test_coalesce_page_ranges_overflow() {
+        let result = coalesce_page_ranges(&[(0, 1), (usize::MAX, 2)], 2);
+
+        assert!(matches!(result, Err(PageBlobError::BlobTooLarge { .. })));
+    }
+
+    #[test]
+    fn test_overlapping_page_ranges() {
+        let result = merge_page_ranges(vec![
+            (0, Bytes::from(vec![0u8; 2 * BLOB_PAGE_SIZE])),
+            (1, Bytes::from(vec![1u8; 10])),
+        ]);
+
+        assert!(matches!(
+            result,
+            Err(PageBlobError::OverlappingRanges { page_no: 1 })
+        ));
+    }
+}
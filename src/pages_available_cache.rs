@@ -0,0 +1,117 @@
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PagesAvailableCachePolicy {
+    // Blob size is read once and is trusted until the blob is resized or deleted by this instance
+    Forever,
+    // Cached size is trusted until a write does not fit into it, then it is read again once
+    RefreshOnRangeError,
+    Ttl(Duration),
+    // Blob properties are requested on every check with the cached ETag,
+    // the size is read again only if the blob is changed since
+    ETagValidated,
+}
+
+impl PagesAvailableCachePolicy {
+    pub fn refresh_on_range_error(&self) -> bool {
+        !matches!(
+            self,
+            PagesAvailableCachePolicy::Forever | PagesAvailableCachePolicy::ETagValidated
+        )
+    }
+}
+
+pub struct PagesAvailableCache {
+    value: Option<usize>,
+    // ETag of the blob the value is read from. Sizes set after own writes have none
+    etag: Option<String>,
+    updated: Instant,
+}
+
+impl PagesAvailableCache {
+    pub fn new() -> Self {
+        Self {
+            value: None,
+            etag: None,
+            updated: Instant::now(),
+        }
+    }
+
+    pub fn get(&self, policy: PagesAvailableCachePolicy) -> Option<usize> {
+        match policy {
+            PagesAvailableCachePolicy::Forever | PagesAvailableCachePolicy::RefreshOnRangeError => {
+                self.value
+            }
+            PagesAvailableCachePolicy::Ttl(ttl) => {
+                if self.updated.elapsed() < ttl {
+                    self.value
+                } else {
+                    None
+                }
+            }
+            PagesAvailableCachePolicy::ETagValidated => None,
+        }
+    }
+
+    pub fn get_with_etag(&self) -> Option<(usize, &str)> {
+        match (self.value, &self.etag) {
+            (Some(value), Some(etag)) => Some((value, etag.as_str())),
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, value: usize) {
+        self.value = Some(value);
+        self.etag = None;
+        self.updated = Instant::now();
+    }
+
+    pub fn set_with_etag(&mut self, value: usize, etag: String) {
+        self.set(value);
+        self.etag = Some(etag);
+    }
+
+    pub fn reset(&mut self) {
+        self.value = None;
+        self.etag = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_policies() {
+        let mut cache = PagesAvailableCache::new();
+        assert_eq!(None, cache.get(PagesAvailableCachePolicy::Forever));
+
+        cache.set(5);
+
+        assert_eq!(Some(5), cache.get(PagesAvailableCachePolicy::Forever));
+        assert_eq!(
+            Some(5),
+            cache.get(PagesAvailableCachePolicy::RefreshOnRangeError)
+        );
+        assert_eq!(
+            Some(5),
+            cache.get(PagesAvailableCachePolicy::Ttl(Duration::from_secs(60)))
+        );
+        assert_eq!(
+            None,
+            cache.get(PagesAvailableCachePolicy::Ttl(Duration::from_secs(0)))
+        );
+
+        assert_eq!(None, cache.get(PagesAvailableCachePolicy::ETagValidated));
+        assert_eq!(None, cache.get_with_etag());
+
+        cache.set_with_etag(6, "\"0x1\"".to_string());
+        assert_eq!(Some((6, "\"0x1\"")), cache.get_with_etag());
+
+        cache.set(7);
+        assert_eq!(None, cache.get_with_etag());
+
+        cache.reset();
+        assert_eq!(None, cache.get(PagesAvailableCachePolicy::Forever));
+    }
+}
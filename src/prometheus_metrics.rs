@@ -0,0 +1,234 @@
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex};
+
+use crate::{PageBlobMetricsSink, PageBlobOperationMetric};
+
+const LATENCY_BUCKETS_SECONDS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct OperationKey {
+    container: String,
+    blob: String,
+    operation: &'static str,
+}
+
+impl OperationKey {
+    fn to_labels(&self) -> String {
+        format!(
+            "container=\"{}\",blob=\"{}\",operation=\"{}\"",
+            escape_label_value(&self.container),
+            escape_label_value(&self.blob),
+            self.operation
+        )
+    }
+}
+
+#[derive(Default)]
+struct OperationStats {
+    requests: u64,
+    bytes_read: u64,
+    bytes_written: u64,
+    latency_buckets: [u64; LATENCY_BUCKETS_SECONDS.len()],
+    latency_sum_seconds: f64,
+    errors: BTreeMap<&'static str, u64>,
+}
+
+pub struct PrometheusMetricsSink {
+    stats: Mutex<BTreeMap<OperationKey, OperationStats>>,
+}
+
+impl Default for PrometheusMetricsSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrometheusMetricsSink {
+    pub fn new() -> Self {
+        Self {
+            stats: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let stats = self.stats.lock().unwrap();
+        let mut result = String::new();
+
+        result.push_str("# TYPE page_blob_requests_total counter\n");
+        for (key, stats) in stats.iter() {
+            writeln!(
+                result,
+                "page_blob_requests_total{{{}}} {}",
+                key.to_labels(),
+                stats.requests
+            )
+            .unwrap();
+        }
+
+        result.push_str("# TYPE page_blob_errors_total counter\n");
+        for (key, stats) in stats.iter() {
+            for (error_kind, errors) in &stats.errors {
+                writeln!(
+                    result,
+                    "page_blob_errors_total{{{},error_kind=\"{}\"}} {}",
+                    key.to_labels(),
+                    escape_label_value(error_kind),
+                    errors
+                )
+                .unwrap();
+            }
+        }
+
+        result.push_str("# TYPE page_blob_read_bytes_total counter\n");
+        for (key, stats) in stats.iter() {
+            writeln!(
+                result,
+                "page_blob_read_bytes_total{{{}}} {}",
+                key.to_labels(),
+                stats.bytes_read
+            )
+            .unwrap();
+        }
+
+        result.push_str("# TYPE page_blob_written_bytes_total counter\n");
+        for (key, stats) in stats.iter() {
+            writeln!(
+                result,
+                "page_blob_written_bytes_total{{{}}} {}",
+                key.to_labels(),
+                stats.bytes_written
+            )
+            .unwrap();
+        }
+
+        result.push_str("# TYPE page_blob_request_duration_seconds histogram\n");
+        for (key, stats) in stats.iter() {
+            let labels = key.to_labels();
+
+            for (index, bucket) in LATENCY_BUCKETS_SECONDS.iter().enumerate() {
+                writeln!(
+                    result,
+                    "page_blob_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, bucket, stats.latency_buckets[index]
+                )
+                .unwrap();
+            }
+
+            writeln!(
+                result,
+                "page_blob_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+                labels, stats.requests
+            )
+            .unwrap();
+
+            writeln!(
+                result,
+                "page_blob_request_duration_seconds_sum{{{}}} {}",
+                labels, stats.latency_sum_seconds
+            )
+            .unwrap();
+
+            writeln!(
+                result,
+                "page_blob_request_duration_seconds_count{{{}}} {}",
+                labels, stats.requests
+            )
+            .unwrap();
+        }
+
+        result
+    }
+}
+
+impl PageBlobMetricsSink for PrometheusMetricsSink {
+    fn record_operation(&self, metric: &PageBlobOperationMetric) {
+        let key = OperationKey {
+            container: metric.container.to_string(),
+            blob: metric.blob.to_string(),
+            operation: metric.operation,
+        };
+
+        let mut stats = self.stats.lock().unwrap();
+        let stats = stats.entry(key).or_default();
+
+        stats.requests += 1;
+        stats.bytes_read += metric.bytes_read as u64;
+        stats.bytes_written += metric.bytes_written as u64;
+
+        let duration_seconds = metric.duration.as_secs_f64();
+        stats.latency_sum_seconds += duration_seconds;
+
+        // Buckets are cumulative
+        for (index, bucket) in LATENCY_BUCKETS_SECONDS.iter().enumerate() {
+            if duration_seconds <= *bucket {
+                stats.latency_buckets[index] += 1;
+            }
+        }
+
+        if let Some(error_kind) = &metric.error_kind {
+            *stats.errors.entry(error_kind).or_default() += 1;
+        }
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let sink = PrometheusMetricsSink::new();
+
+        sink.record_operation(&PageBlobOperationMetric {
+            container: "container",
+            blob: "tenant/\"1\"",
+            operation: "get",
+            duration: Duration::from_millis(20),
+            bytes_read: 1024,
+            bytes_written: 0,
+            error_kind: None,
+        });
+
+        sink.record_operation(&PageBlobOperationMetric {
+            container: "container",
+            blob: "tenant/\"1\"",
+            operation: "get",
+            duration: Duration::from_millis(200),
+            bytes_read: 0,
+            bytes_written: 0,
+            error_kind: Some("BlobNotFound"),
+        });
+
+        let result = sink.render();
+        let labels = "container=\"container\",blob=\"tenant/\\\"1\\\"\",operation=\"get\"";
+
+        assert!(result.contains(&format!("page_blob_requests_total{{{}}} 2\n", labels)));
+        assert!(result.contains(&format!(
+            "page_blob_errors_total{{{},error_kind=\"BlobNotFound\"}} 1\n",
+            labels
+        )));
+        assert!(result.contains(&format!("page_blob_read_bytes_total{{{}}} 1024\n", labels)));
+        assert!(result.contains(&format!(
+            "page_blob_request_duration_seconds_bucket{{{},le=\"0.025\"}} 1\n",
+            labels
+        )));
+        assert!(result.contains(&format!(
+            "page_blob_request_duration_seconds_bucket{{{},le=\"0.25\"}} 2\n",
+            labels
+        )));
+        assert!(result.contains(&format!(
+            "page_blob_request_duration_seconds_count{{{}}} 2\n",
+            labels
+        )));
+    }
+}
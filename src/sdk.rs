@@ -1,77 +1,145 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, future::Future, ops::Range, sync::Arc};
 
+use bytes::{Bytes, BytesMut};
+use futures::{stream, StreamExt};
 use my_azure_storage_sdk::{
     blob::BlobProperties, page_blob::consts::BLOB_PAGE_SIZE, AzureStorageConnectionInfo,
     AzureStorageError,
 };
 use my_telemetry::MyTelemetry;
 
+use crate::{
+    blob_rest::BlobRestConnection,
+    deadline::execute_with_deadline,
+    limits::{
+        check_blob_pages_amount, check_page_aligned, clamp_blob_pages_amount,
+        clamp_max_pages_to_write, get_end_page_no,
+    },
+    page_ranges::{
+        coalesce_page_ranges, get_page_range_results, get_pages_amount_needs, merge_page_ranges,
+        split_coalesced_payloads,
+    },
+    pages_available_cache::PagesAvailableCache,
+    GrowthStrategy, PageBlobError, PageBlobOptions, PageRangeResult, PagesAvailableCachePolicy,
+    SequenceNumberAction, SequenceNumberCondition, WriteProgress,
+};
+
 pub struct MyAzurePageBlobSdk {
     pub container_name: String,
     pub blob_name: String,
-    pages_available: Option<usize>,
+    pub options: PageBlobOptions,
+    // Sequence numbers, metadata, page ranges and ETags are not covered by the sdk
+    rest_connection: Arc<BlobRestConnection>,
+    pages_available: PagesAvailableCache,
 }
 
 impl MyAzurePageBlobSdk {
-    pub fn new(container_name: String, blob_name: String) -> Self {
+    pub fn new(
+        container_name: String,
+        blob_name: String,
+        options: PageBlobOptions,
+        rest_connection: Arc<BlobRestConnection>,
+    ) -> Self {
         Self {
             container_name,
             blob_name,
-            pages_available: None,
+            options,
+            rest_connection,
+            pages_available: PagesAvailableCache::new(),
         }
     }
     #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name,
+                pages_amount
+            )
+        )
+    )]
     pub async fn resize<'s, TMyTelemetry: MyTelemetry>(
         &mut self,
         connection: &AzureStorageConnectionInfo,
         my_telemetry: Option<Arc<TMyTelemetry>>,
         pages_amount: usize,
-    ) -> Result<(), AzureStorageError> {
-        my_azure_storage_sdk::page_blob::sdk::resize_page_blob(
-            connection,
-            self.container_name.as_str(),
-            self.blob_name.as_str(),
-            pages_amount,
-            my_telemetry,
-        )
+    ) -> Result<(), PageBlobError> {
+        check_blob_pages_amount(pages_amount)?;
+
+        execute_round_trip(&self.options, || {
+            my_azure_storage_sdk::page_blob::sdk::resize_page_blob(
+                connection,
+                self.container_name.as_str(),
+                self.blob_name.as_str(),
+                pages_amount,
+                my_telemetry.clone(),
+            )
+        })
         .await?;
 
-        self.pages_available = Some(pages_amount);
+        self.pages_available.set(pages_amount);
 
         Ok(())
     }
     #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name
+            )
+        )
+    )]
     pub async fn create_container_if_not_exist<'s, TMyTelemetry: MyTelemetry>(
         &mut self,
         connection: &AzureStorageConnectionInfo,
         my_telemetry: Option<Arc<TMyTelemetry>>,
-    ) -> Result<(), AzureStorageError> {
-        my_azure_storage_sdk::blob_container::sdk::create_container_if_not_exist(
-            connection,
-            self.container_name.as_str(),
-            my_telemetry,
-        )
+    ) -> Result<(), PageBlobError> {
+        execute_round_trip(&self.options, || {
+            my_azure_storage_sdk::blob_container::sdk::create_container_if_not_exist(
+                connection,
+                self.container_name.as_str(),
+                my_telemetry.clone(),
+            )
+        })
         .await?;
 
         Ok(())
     }
     #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name
+            )
+        )
+    )]
     pub async fn read_blob_size<'s, TMyTelemetry: MyTelemetry>(
         &mut self,
         connection: &AzureStorageConnectionInfo,
         my_telemetry: Option<Arc<TMyTelemetry>>,
-    ) -> Result<usize, AzureStorageError> {
-        let props = my_azure_storage_sdk::blob::sdk::get_blob_properties(
-            &connection,
-            self.container_name.as_str(),
-            self.blob_name.as_str(),
-            my_telemetry,
-        )
+    ) -> Result<usize, PageBlobError> {
+        let props = execute_round_trip(&self.options, || {
+            my_azure_storage_sdk::blob::sdk::get_blob_properties(
+                connection,
+                self.container_name.as_str(),
+                self.blob_name.as_str(),
+                my_telemetry.clone(),
+            )
+        })
         .await?;
 
+        check_page_aligned(props.blob_size)?;
+
         let result = props.blob_size / BLOB_PAGE_SIZE;
 
-        self.pages_available = Some(result);
+        self.pages_available.set(result);
 
         return Ok(result);
     }
@@ -80,8 +148,12 @@ impl MyAzurePageBlobSdk {
         &mut self,
         connection: &AzureStorageConnectionInfo,
         my_telemetry: Option<Arc<TMyTelemetry>>,
-    ) -> Result<usize, AzureStorageError> {
-        match self.pages_available {
+    ) -> Result<usize, PageBlobError> {
+        if self.options.pages_available_cache == PagesAvailableCachePolicy::ETagValidated {
+            return self.read_blob_size_if_changed().await;
+        }
+
+        match self.pages_available.get(self.options.pages_available_cache) {
             Some(result) => {
                 return Ok(result);
             }
@@ -91,163 +163,427 @@ impl MyAzurePageBlobSdk {
             }
         }
     }
+    async fn read_blob_size_if_changed(&mut self) -> Result<usize, PageBlobError> {
+        let cached = self
+            .pages_available
+            .get_with_etag()
+            .map(|(value, etag)| (value, etag.to_string()));
+
+        let props = match cached {
+            Some((value, etag)) => {
+                let props = execute_round_trip(&self.options, || {
+                    self.rest_connection.get_blob_properties_if_none_match(
+                        &self.container_name,
+                        &self.blob_name,
+                        &etag,
+                    )
+                })
+                .await?;
+
+                match props {
+                    Some(props) => props,
+                    None => return Ok(value),
+                }
+            }
+            None => {
+                execute_round_trip(&self.options, || {
+                    self.rest_connection
+                        .get_blob_properties(&self.container_name, &self.blob_name)
+                })
+                .await?
+            }
+        };
+
+        check_page_aligned(props.blob_size)?;
+
+        let result = props.blob_size / BLOB_PAGE_SIZE;
+
+        self.pages_available.set_with_etag(result, props.etag);
+
+        Ok(result)
+    }
+    // Cached size can be stale if the blob is resized by another writer,
+    // so it is read again before a range error is reported
+    async fn get_fitting_pages_amount<TMyTelemetry: MyTelemetry>(
+        &mut self,
+        connection: &AzureStorageConnectionInfo,
+        my_telemetry: Option<Arc<TMyTelemetry>>,
+        pages_amount_needs: usize,
+    ) -> Result<usize, PageBlobError> {
+        let available_pages_amount = self
+            .get_available_pages_amount(connection, my_telemetry.clone())
+            .await?;
+
+        if pages_amount_needs <= available_pages_amount
+            || !self.options.pages_available_cache.refresh_on_range_error()
+        {
+            return Ok(available_pages_amount);
+        }
+
+        return self.read_blob_size(connection, my_telemetry).await;
+    }
     #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name,
+                pages_amount
+            )
+        )
+    )]
     pub async fn create<'s, TMyTelemetry: MyTelemetry>(
         &mut self,
         connection: &AzureStorageConnectionInfo,
         my_telemetry: Option<Arc<TMyTelemetry>>,
         pages_amount: usize,
-    ) -> Result<(), AzureStorageError> {
-        my_azure_storage_sdk::page_blob::sdk::create_page_blob(
-            connection,
-            self.container_name.as_str(),
-            &self.blob_name,
-            pages_amount,
-            my_telemetry,
-        )
+    ) -> Result<(), PageBlobError> {
+        check_blob_pages_amount(pages_amount)?;
+
+        execute_round_trip(&self.options, || {
+            my_azure_storage_sdk::page_blob::sdk::create_page_blob(
+                connection,
+                self.container_name.as_str(),
+                &self.blob_name,
+                pages_amount,
+                my_telemetry.clone(),
+            )
+        })
         .await?;
 
-        self.pages_available = Some(pages_amount);
+        self.pages_available.set(pages_amount);
 
         return Ok(());
     }
     #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name,
+                pages_amount
+            )
+        )
+    )]
     pub async fn create_if_not_exists<'s, TMyTelemetry: MyTelemetry>(
         &mut self,
         connection: &AzureStorageConnectionInfo,
         my_telemetry: Option<Arc<TMyTelemetry>>,
         pages_amount: usize,
-    ) -> Result<(), AzureStorageError> {
-        let props = my_azure_storage_sdk::page_blob::sdk::create_page_blob_if_not_exists(
-            connection,
-            self.container_name.as_str(),
-            &self.blob_name,
-            pages_amount,
-            my_telemetry,
-        )
+    ) -> Result<(), PageBlobError> {
+        check_blob_pages_amount(pages_amount)?;
+
+        let props = execute_round_trip(&self.options, || {
+            my_azure_storage_sdk::page_blob::sdk::create_page_blob_if_not_exists(
+                connection,
+                self.container_name.as_str(),
+                &self.blob_name,
+                pages_amount,
+                my_telemetry.clone(),
+            )
+        })
         .await?;
 
         let result = props.blob_size / BLOB_PAGE_SIZE;
-        self.pages_available = Some(result);
+        self.pages_available.set(result);
 
         return Ok(());
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name,
+                start_page_no,
+                pages_amount
+            )
+        )
+    )]
     pub async fn get<'s, TMyTelemetry: MyTelemetry>(
-        &mut self,
+        &self,
         connection: &AzureStorageConnectionInfo,
         my_telemetry: Option<Arc<TMyTelemetry>>,
         start_page_no: usize,
         pages_amount: usize,
-    ) -> Result<Vec<u8>, AzureStorageError> {
-        my_azure_storage_sdk::page_blob::sdk::get_pages(
+    ) -> Result<Bytes, PageBlobError> {
+        let result = execute_round_trip(&self.options, || {
+            my_azure_storage_sdk::page_blob::sdk::get_pages(
+                connection,
+                self.container_name.as_str(),
+                self.blob_name.as_str(),
+                start_page_no,
+                pages_amount,
+                my_telemetry.clone(),
+            )
+        })
+        .await?;
+
+        // Azure answers a range crossing the end of the blob with the truncated content
+        if result.len() < pages_amount.saturating_mul(BLOB_PAGE_SIZE) {
+            return Err(PageBlobError::OutOfRange {
+                requested: start_page_no.saturating_add(pages_amount),
+                available: start_page_no + result.len() / BLOB_PAGE_SIZE,
+            });
+        }
+
+        Ok(Bytes::from(result))
+    }
+    #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name,
+                ranges = ranges.len()
+            )
+        )
+    )]
+    pub async fn get_page_ranges_batch<TMyTelemetry: MyTelemetry>(
+        &mut self,
+        connection: &AzureStorageConnectionInfo,
+        my_telemetry: Option<Arc<TMyTelemetry>>,
+        ranges: &[(usize, usize)],
+    ) -> Result<Vec<Bytes>, PageBlobError> {
+        let coalesced_ranges = coalesce_page_ranges(ranges, self.options.max_read_gap_pages)?;
+
+        let this = &*self;
+
+        // Collected to owned values, otherwise the future is not Send for async_trait
+        let round_trips = coalesced_ranges
+            .iter()
+            .map(|coalesced| (coalesced.start_page_no, coalesced.pages_amount))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|(start_page_no, pages_amount)| {
+                this.get(
+                    connection,
+                    my_telemetry.clone(),
+                    start_page_no,
+                    pages_amount,
+                )
+            });
+
+        let mut round_trips =
+            stream::iter(round_trips).buffered(self.options.max_concurrent_requests.max(1));
+
+        let mut payloads = Vec::with_capacity(coalesced_ranges.len());
+
+        while let Some(payload) = round_trips.next().await {
+            payloads.push(payload?);
+        }
+
+        split_coalesced_payloads(ranges, &coalesced_ranges, payloads)
+    }
+    #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name,
+                start_page_no,
+                bytes = payload.len()
+            )
+        )
+    )]
+    pub async fn save_pages<'s, TMyTelemetry: MyTelemetry>(
+        &mut self,
+        connection: &AzureStorageConnectionInfo,
+        my_telemetry: Option<Arc<TMyTelemetry>>,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+        write_progress: &WriteProgress,
+    ) -> Result<usize, PageBlobError> {
+        self.save_pages_with_condition(
             connection,
-            self.container_name.as_str(),
-            self.blob_name.as_str(),
-            start_page_no,
-            pages_amount,
             my_telemetry,
+            None,
+            start_page_no,
+            max_pages_to_write,
+            payload,
+            write_progress,
         )
         .await
     }
-    #[inline]
-    pub async fn save_pages<'s, TMyTelemetry: MyTelemetry>(
+
+    // Every chunk is written with the sequence number condition, so a writer fenced
+    // in the middle of the write stops at the first chunk after the fence
+    #[allow(clippy::too_many_arguments)]
+    async fn save_pages_with_condition<TMyTelemetry: MyTelemetry>(
         &mut self,
         connection: &AzureStorageConnectionInfo,
         my_telemetry: Option<Arc<TMyTelemetry>>,
+        condition: Option<SequenceNumberCondition>,
         start_page_no: usize,
         max_pages_to_write: usize,
-        mut payload: Vec<u8>,
-    ) -> Result<usize, AzureStorageError> {
+        payload: Bytes,
+        write_progress: &WriteProgress,
+    ) -> Result<usize, PageBlobError> {
+        if payload.is_empty() {
+            return Ok(0);
+        }
+
+        let max_pages_to_write = clamp_max_pages_to_write(max_pages_to_write);
         let max_write_chunk = BLOB_PAGE_SIZE * max_pages_to_write;
 
-        ressize_payload_to_fullpage(&mut payload);
+        let payload = ressize_payload_to_fullpage(payload);
 
         let result = payload.len();
 
-        let pages_amount_after_append = get_pages_amount_after_append(start_page_no, payload.len());
-
-        let available_pages_amount = self
-            .get_available_pages_amount(connection, my_telemetry.clone())
-            .await?;
+        let pages_amount_after_append =
+            match get_pages_amount_after_append(start_page_no, payload.len()) {
+                Ok(result) => result,
+                Err(err) => {
+                    write_progress.set_failed_pages(start_page_no..usize::MAX);
+                    return Err(err);
+                }
+            };
 
-        if pages_amount_after_append > available_pages_amount {
-            return Err(AzureStorageError::UnknownError {msg : format!("Can not save pages. Requires blob with the pages amount: {}. Available pages amount is: {}", pages_amount_after_append, available_pages_amount)});
+        if let Err(err) = check_blob_pages_amount(pages_amount_after_append) {
+            write_progress.set_failed_pages(start_page_no..pages_amount_after_append);
+            return Err(err);
         }
 
-        if payload.len() <= max_write_chunk {
-            my_azure_storage_sdk::page_blob::sdk::save_pages(
-                connection,
-                self.container_name.as_str(),
-                self.blob_name.as_str(),
-                start_page_no,
-                payload,
-                my_telemetry,
-            )
-            .await?;
+        let available_pages_amount = match self
+            .get_fitting_pages_amount(connection, my_telemetry.clone(), pages_amount_after_append)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                write_progress.set_failed_pages(start_page_no..pages_amount_after_append);
+                return Err(err);
+            }
+        };
 
-            return Ok(result);
+        if pages_amount_after_append > available_pages_amount {
+            write_progress.set_failed_pages(start_page_no..pages_amount_after_append);
+            return Err(PageBlobError::OutOfRange {
+                requested: pages_amount_after_append,
+                available: available_pages_amount,
+            });
         }
 
-        let mut remains_len = payload.len();
-        let mut pos = 0;
-        let mut start_page_no = start_page_no;
-
-        while remains_len > 0 {
-            let write_amount = if remains_len > max_write_chunk {
-                max_write_chunk
-            } else {
-                remains_len
-            };
-
-            let payload_to_write = &payload[pos..pos + write_amount];
-
-            println!(
-                "Debbug: {}/{} Writing chunk to the page {} with size {} to blob",
-                self.container_name.as_str(),
-                self.blob_name.as_str(),
-                start_page_no,
-                payload_to_write.len()
-            );
-
-            my_azure_storage_sdk::page_blob::sdk::save_pages(
-                connection,
-                self.container_name.as_str(),
-                self.blob_name.as_str(),
-                start_page_no,
-                payload_to_write.to_vec(),
-                my_telemetry.clone(),
-            )
-            .await?;
+        let this = &*self;
+
+        // Chunks are split off the payload, so a single chunk is the payload itself
+        let mut payload = payload;
+        let chunks_amount = payload.len().div_ceil(max_write_chunk);
+        let chunks: Vec<Bytes> = (0..chunks_amount)
+            .map(|_| payload.split_to(max_write_chunk.min(payload.len())))
+            .collect();
+
+        let round_trips = chunks
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_index, payload_to_write)| {
+                let start_page_no = start_page_no + chunk_index * max_pages_to_write;
+                let chunk_pages =
+                    start_page_no..start_page_no + payload_to_write.len() / BLOB_PAGE_SIZE;
+                let my_telemetry = my_telemetry.clone();
+
+                async move {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(
+                        chunk_index,
+                        start_page_no,
+                        bytes = payload_to_write.len(),
+                        "Writing chunk"
+                    );
+
+                    let result = this
+                        .save_chunk(
+                            connection,
+                            my_telemetry,
+                            condition,
+                            start_page_no,
+                            payload_to_write,
+                        )
+                        .await;
+
+                    (chunk_pages, result)
+                }
+            });
+
+        let mut round_trips =
+            stream::iter(round_trips).buffered(self.options.max_concurrent_requests.max(1));
+
+        // Chunks are yielded in order, so pages_written only covers the contiguous committed
+        // prefix even if later chunks are already committed
+        let mut pages_written = 0;
+
+        while let Some((chunk_pages, round_trip_result)) = round_trips.next().await {
+            if let Err(err) = round_trip_result {
+                write_progress.set_failed_pages(chunk_pages.clone());
+
+                if chunks_amount == 1 {
+                    return Err(err);
+                }
+
+                return Err(PageBlobError::PartialWrite {
+                    pages_written,
+                    failed_pages: chunk_pages,
+                    err: Box::new(err),
+                });
+            }
 
-            pos += write_amount;
-            remains_len -= write_amount;
-            start_page_no += write_amount / BLOB_PAGE_SIZE;
+            pages_written += chunk_pages.len();
+            write_progress.add_bytes_written(chunk_pages.len() * BLOB_PAGE_SIZE);
         }
 
         Ok(result)
     }
     #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name,
+                start_page_no,
+                bytes = payload.len()
+            )
+        )
+    )]
     pub async fn auto_ressize_and_save_pages<'s, TMyTelemetry: MyTelemetry>(
         &mut self,
         connection: &AzureStorageConnectionInfo,
         my_telemetry: Option<Arc<TMyTelemetry>>,
         start_page_no: usize,
         max_pages_to_write_single_round_trip: usize,
-        mut payload: Vec<u8>,
-        resize_pages_ration: usize,
-    ) -> Result<usize, AzureStorageError> {
-        ressize_payload_to_fullpage(&mut payload);
+        payload: Bytes,
+        growth_strategy: &dyn GrowthStrategy,
+        write_progress: &WriteProgress,
+    ) -> Result<usize, PageBlobError> {
+        if payload.is_empty() {
+            return Ok(0);
+        }
+
+        let payload = ressize_payload_to_fullpage(payload);
 
-        let pages_amount_after_append = get_pages_amount_after_append(start_page_no, payload.len());
+        let pages_amount_after_append =
+            get_pages_amount_after_append(start_page_no, payload.len())?;
 
         let available_pages_amount = self
-            .get_available_pages_amount(connection, my_telemetry.clone())
+            .get_fitting_pages_amount(connection, my_telemetry.clone(), pages_amount_after_append)
             .await?;
 
         if pages_amount_after_append > available_pages_amount {
-            let pages_amount_needes =
-                get_ressize_to_pages_amount(pages_amount_after_append, resize_pages_ration);
+            let pages_amount_needes = clamp_blob_pages_amount(
+                growth_strategy
+                    .get_pages_amount_to_resize(available_pages_amount, pages_amount_after_append),
+            );
 
             self.resize(connection, my_telemetry.clone(), pages_amount_needes)
                 .await?;
@@ -260,109 +596,602 @@ impl MyAzurePageBlobSdk {
                 start_page_no,
                 max_pages_to_write_single_round_trip,
                 payload,
+                write_progress,
             )
             .await?;
 
         return Ok(result);
     }
+
+    async fn save_chunk<TMyTelemetry: MyTelemetry>(
+        &self,
+        connection: &AzureStorageConnectionInfo,
+        my_telemetry: Option<Arc<TMyTelemetry>>,
+        condition: Option<SequenceNumberCondition>,
+        start_page_no: usize,
+        payload: Bytes,
+    ) -> Result<(), PageBlobError> {
+        if let Some(condition) = condition {
+            return execute_round_trip(&self.options, || {
+                self.rest_connection.save_pages(
+                    &self.container_name,
+                    &self.blob_name,
+                    start_page_no,
+                    payload.clone(),
+                    Some(condition),
+                )
+            })
+            .await;
+        }
+
+        // Not copied if the chunk is the only reference to its buffer
+        let body = Vec::from(payload);
+
+        execute_round_trip_with_body(&self.options, body, |body| {
+            my_azure_storage_sdk::page_blob::sdk::save_pages(
+                connection,
+                self.container_name.as_str(),
+                self.blob_name.as_str(),
+                start_page_no,
+                body,
+                my_telemetry.clone(),
+            )
+        })
+        .await
+    }
     #[inline]
-    pub async fn delete<'s, TMyTelemetry: MyTelemetry>(
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name,
+                ranges = ranges.len()
+            )
+        )
+    )]
+    pub async fn save_page_ranges<TMyTelemetry: MyTelemetry>(
         &mut self,
         connection: &AzureStorageConnectionInfo,
         my_telemetry: Option<Arc<TMyTelemetry>>,
-    ) -> Result<(), AzureStorageError> {
-        my_azure_storage_sdk::blob::sdk::delete_blob(
-            connection,
-            self.container_name.as_str(),
-            self.blob_name.as_str(),
-            my_telemetry,
+        ranges: Vec<(usize, Bytes)>,
+    ) -> Result<Vec<PageRangeResult>, PageBlobError> {
+        let ranges_amount = ranges.len();
+        let merged_ranges = merge_page_ranges(ranges)?;
+
+        if merged_ranges.is_empty() {
+            return Ok(get_page_range_results(
+                ranges_amount,
+                &merged_ranges,
+                vec![],
+            ));
+        }
+
+        let pages_amount_needs = get_pages_amount_needs(&merged_ranges);
+
+        check_blob_pages_amount(pages_amount_needs)?;
+
+        let available_pages_amount = self
+            .get_fitting_pages_amount(connection, my_telemetry.clone(), pages_amount_needs)
+            .await?;
+
+        if pages_amount_needs > available_pages_amount {
+            return Err(PageBlobError::OutOfRange {
+                requested: pages_amount_needs,
+                available: available_pages_amount,
+            });
+        }
+
+        let max_pages_to_write = clamp_max_pages_to_write(self.options.max_pages_to_write);
+        let max_write_chunk = BLOB_PAGE_SIZE * max_pages_to_write;
+
+        let this = &*self;
+
+        // Chunks of all the ranges share the same limit of concurrent requests
+        let mut chunks = Vec::new();
+
+        for (merged_index, merged_range) in merged_ranges.iter().enumerate() {
+            let payload = &merged_range.payload;
+
+            for chunk_index in 0..payload.len().div_ceil(max_write_chunk) {
+                let pos = chunk_index * max_write_chunk;
+                chunks.push((
+                    merged_index,
+                    merged_range.start_page_no + chunk_index * max_pages_to_write,
+                    payload.slice(pos..payload.len().min(pos + max_write_chunk)),
+                ));
+            }
+        }
+
+        let round_trips = chunks
+            .into_iter()
+            .map(|(merged_index, start_page_no, payload)| {
+                let my_telemetry = my_telemetry.clone();
+
+                async move {
+                    let result = this
+                        .save_chunk(connection, my_telemetry, None, start_page_no, payload)
+                        .await;
+
+                    (merged_index, result)
+                }
+            });
+
+        let mut round_trips =
+            stream::iter(round_trips).buffer_unordered(self.options.max_concurrent_requests.max(1));
+
+        let mut results: Vec<Result<(), PageBlobError>> =
+            merged_ranges.iter().map(|_| Ok(())).collect();
+
+        while let Some((merged_index, round_trip_result)) = round_trips.next().await {
+            if let Err(err) = round_trip_result {
+                if results[merged_index].is_ok() {
+                    results[merged_index] = Err(err);
+                }
+            }
+        }
+
+        Ok(get_page_range_results(
+            ranges_amount,
+            &merged_ranges,
+            results,
+        ))
+    }
+    #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name
+            )
         )
+    )]
+    pub async fn delete<'s, TMyTelemetry: MyTelemetry>(
+        &mut self,
+        connection: &AzureStorageConnectionInfo,
+        my_telemetry: Option<Arc<TMyTelemetry>>,
+    ) -> Result<(), PageBlobError> {
+        execute_round_trip(&self.options, || {
+            my_azure_storage_sdk::blob::sdk::delete_blob(
+                connection,
+                self.container_name.as_str(),
+                self.blob_name.as_str(),
+                my_telemetry.clone(),
+            )
+        })
         .await?;
 
-        self.pages_available = None;
+        self.pages_available.reset();
         Ok(())
     }
     #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name
+            )
+        )
+    )]
     pub async fn delete_if_exists<'s, TMyTelemetry: MyTelemetry>(
         &mut self,
         connection: &AzureStorageConnectionInfo,
         my_telemetry: Option<Arc<TMyTelemetry>>,
-    ) -> Result<(), AzureStorageError> {
-        my_azure_storage_sdk::blob::sdk::delete_blob_if_exists(
-            connection,
-            self.container_name.as_str(),
-            self.blob_name.as_str(),
-            my_telemetry,
-        )
+    ) -> Result<(), PageBlobError> {
+        execute_round_trip(&self.options, || {
+            my_azure_storage_sdk::blob::sdk::delete_blob_if_exists(
+                connection,
+                self.container_name.as_str(),
+                self.blob_name.as_str(),
+                my_telemetry.clone(),
+            )
+        })
         .await?;
 
-        self.pages_available = None;
+        self.pages_available.reset();
         Ok(())
     }
 
     #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name
+            )
+        )
+    )]
     pub async fn download<TMyTelemetry: MyTelemetry>(
         &mut self,
         connection: &AzureStorageConnectionInfo,
         my_telemetry: Option<Arc<TMyTelemetry>>,
-    ) -> Result<Vec<u8>, AzureStorageError> {
-        my_azure_storage_sdk::blob::sdk::download_blob(
-            connection,
-            self.container_name.as_str(),
-            self.blob_name.as_str(),
-            my_telemetry,
+    ) -> Result<Bytes, PageBlobError> {
+        let result = execute_round_trip(&self.options, || {
+            my_azure_storage_sdk::blob::sdk::download_blob(
+                connection,
+                self.container_name.as_str(),
+                self.blob_name.as_str(),
+                my_telemetry.clone(),
+            )
+        })
+        .await?;
+
+        Ok(Bytes::from(result))
+    }
+
+    #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name
+            )
+        )
+    )]
+    pub async fn get_blob_properties<TMyTelemetry: MyTelemetry>(
+        &mut self,
+        connection: &AzureStorageConnectionInfo,
+        my_telemetry: Option<Arc<TMyTelemetry>>,
+    ) -> Result<BlobProperties, PageBlobError> {
+        let result = execute_round_trip(&self.options, || {
+            my_azure_storage_sdk::blob::sdk::get_blob_properties(
+                connection,
+                self.container_name.as_str(),
+                self.blob_name.as_str(),
+                my_telemetry.clone(),
+            )
+        })
+        .await?;
+
+        Ok(result)
+    }
+
+    #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name
+            )
         )
+    )]
+    pub async fn get_sequence_number(&mut self) -> Result<u64, PageBlobError> {
+        let props = execute_round_trip(&self.options, || {
+            self.rest_connection
+                .get_blob_properties(&self.container_name, &self.blob_name)
+        })
+        .await?;
+
+        Ok(props.sequence_number)
+    }
+
+    #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name
+            )
+        )
+    )]
+    pub async fn update_sequence_number(
+        &mut self,
+        action: SequenceNumberAction,
+    ) -> Result<u64, PageBlobError> {
+        // Validated before the request, Azure rejects values above it with a less clear error
+        action.apply(0)?;
+
+        execute_round_trip(&self.options, || {
+            self.rest_connection.update_sequence_number(
+                &self.container_name,
+                &self.blob_name,
+                action,
+            )
+        })
         .await
     }
 
+    // Azure does not check sequence number conditions on resize, so the condition is checked
+    // against the read properties and the resize is conditional on their ETag
     #[inline]
-    pub async fn get_blob_properties<TMyTelemetry: MyTelemetry>(
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name,
+                pages_amount
+            )
+        )
+    )]
+    pub async fn resize_if_sequence_number(
+        &mut self,
+        condition: SequenceNumberCondition,
+        pages_amount: usize,
+    ) -> Result<(), PageBlobError> {
+        check_blob_pages_amount(pages_amount)?;
+
+        let props = execute_round_trip(&self.options, || {
+            self.rest_connection
+                .get_blob_properties(&self.container_name, &self.blob_name)
+        })
+        .await?;
+
+        condition.check(props.sequence_number)?;
+
+        execute_round_trip(&self.options, || {
+            self.rest_connection.resize_if_match(
+                &self.container_name,
+                &self.blob_name,
+                pages_amount,
+                &props.etag,
+            )
+        })
+        .await?;
+
+        self.pages_available.set(pages_amount);
+
+        Ok(())
+    }
+
+    #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name,
+                start_page_no,
+                bytes = payload.len()
+            )
+        )
+    )]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn save_pages_if_sequence_number<TMyTelemetry: MyTelemetry>(
         &mut self,
         connection: &AzureStorageConnectionInfo,
         my_telemetry: Option<Arc<TMyTelemetry>>,
-    ) -> Result<BlobProperties, AzureStorageError> {
-        my_azure_storage_sdk::blob::sdk::get_blob_properties(
+        condition: SequenceNumberCondition,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+    ) -> Result<usize, PageBlobError> {
+        self.save_pages_with_condition(
             connection,
-            self.container_name.as_ref(),
-            self.blob_name.as_ref(),
             my_telemetry,
+            Some(condition),
+            start_page_no,
+            max_pages_to_write,
+            payload,
+            &WriteProgress::new(),
+        )
+        .await
+    }
+
+    #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name
+            )
+        )
+    )]
+    pub async fn get_written_page_ranges(&mut self) -> Result<Vec<Range<usize>>, PageBlobError> {
+        let mut result: Vec<Range<usize>> = Vec::new();
+        let mut marker = None;
+
+        loop {
+            let (ranges, next_marker) = execute_round_trip(&self.options, || {
+                self.rest_connection.get_page_ranges_segment(
+                    &self.container_name,
+                    &self.blob_name,
+                    marker.as_deref(),
+                )
+            })
+            .await?;
+
+            for range in ranges {
+                match result.last_mut() {
+                    Some(last) if last.end == range.start => last.end = range.end,
+                    _ => result.push(range),
+                }
+            }
+
+            match next_marker {
+                Some(next_marker) => marker = Some(next_marker),
+                None => return Ok(result),
+            }
+        }
+    }
+
+    #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name
+            )
+        )
+    )]
+    pub async fn get_metadata(&mut self) -> Result<BTreeMap<String, String>, PageBlobError> {
+        execute_round_trip(&self.options, || {
+            self.rest_connection
+                .get_blob_metadata(&self.container_name, &self.blob_name)
+        })
+        .await
+    }
+
+    #[inline]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                container = %self.container_name,
+                blob = %self.blob_name
+            )
         )
+    )]
+    pub async fn set_metadata(
+        &mut self,
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<(), PageBlobError> {
+        execute_round_trip(&self.options, || {
+            self.rest_connection
+                .set_blob_metadata(&self.container_name, &self.blob_name, metadata)
+        })
         .await
     }
 }
 
-pub fn ressize_payload_to_fullpage(payload: &mut Vec<u8>) {
-    let mut remains_to_resize = get_full_pages_size(payload.len()) - payload.len();
+pub(crate) async fn execute_round_trip<
+    TResult,
+    TError: Into<PageBlobError>,
+    TFuture: Future<Output = Result<TResult, TError>>,
+    TRoundTrip: Fn() -> TFuture,
+>(
+    options: &PageBlobOptions,
+    round_trip: TRoundTrip,
+) -> Result<TResult, PageBlobError> {
+    execute_attempts(options, |_| round_trip()).await
+}
 
-    while remains_to_resize > 0 {
-        payload.push(0);
-        remains_to_resize -= 1;
+// The sdk consumes the body. Attempts which can still be retried send a copy and the last one
+// takes the body itself, so without retries the body is not copied
+async fn execute_round_trip_with_body<
+    TResult,
+    TError: Into<PageBlobError>,
+    TFuture: Future<Output = Result<TResult, TError>>,
+    TRoundTrip: Fn(Vec<u8>) -> TFuture,
+>(
+    options: &PageBlobOptions,
+    mut body: Vec<u8>,
+    round_trip: TRoundTrip,
+) -> Result<TResult, PageBlobError> {
+    execute_attempts(options, |attempt| {
+        if attempt < options.retries {
+            round_trip(body.clone())
+        } else {
+            round_trip(std::mem::take(&mut body))
+        }
+    })
+    .await
+}
+
+async fn execute_attempts<
+    TResult,
+    TError: Into<PageBlobError>,
+    TFuture: Future<Output = Result<TResult, TError>>,
+    TRoundTrip: FnMut(usize) -> TFuture,
+>(
+    options: &PageBlobOptions,
+    mut round_trip: TRoundTrip,
+) -> Result<TResult, PageBlobError> {
+    let mut attempt = 0;
+
+    loop {
+        let future = round_trip(attempt);
+
+        let result = execute_with_deadline("request", options.request_timeout, async {
+            future.await.map_err(Into::into)
+        })
+        .await;
+
+        match result {
+            Ok(result) => return Ok(result),
+            Err(err) => {
+                if attempt >= options.retries || !is_retriable(&err) {
+                    return Err(err);
+                }
+            }
+        }
+
+        attempt += 1;
+        tokio::time::sleep(options.retry_delay).await;
     }
 }
 
-fn get_full_pages_size(len: usize) -> usize {
-    let pages = (len - 1) / BLOB_PAGE_SIZE;
+// Only transient failures are retried. A retried 4xx fails the same way, and a retried
+// conditional write which timed out can be rejected by its own first attempt
+fn is_retriable(err: &PageBlobError) -> bool {
+    match err {
+        PageBlobError::Timeout { .. } | PageBlobError::Io(_) => true,
+        PageBlobError::ServerError { status, .. } => matches!(status, 500 | 502 | 503 | 504),
+        // The sdk reports unexpected responses as UnknownError with the response in the message
+        PageBlobError::Azure(AzureStorageError::UnknownError { msg }) => {
+            TRANSIENT_ERROR_CODES.iter().any(|code| msg.contains(code))
+        }
+        _ => false,
+    }
+}
 
-    (pages + 1) * BLOB_PAGE_SIZE
+const TRANSIENT_ERROR_CODES: [&str; 3] = ["ServerBusy", "InternalError", "OperationTimedOut"];
+
+// Full page payloads are returned as is, otherwise the payload is copied once to pad it with zeros
+pub fn ressize_payload_to_fullpage(payload: Bytes) -> Bytes {
+    let full_pages_size = get_full_pages_size(payload.len());
+
+    if full_pages_size == payload.len() {
+        return payload;
+    }
+
+    let mut result = BytesMut::with_capacity(full_pages_size);
+    result.extend_from_slice(&payload);
+    result.resize(full_pages_size, 0);
+    return result.freeze();
 }
 
-pub fn get_pages_amount_after_append(start_page_no: usize, data_len: usize) -> usize {
+fn get_full_pages_size(len: usize) -> usize {
+    len.div_ceil(BLOB_PAGE_SIZE) * BLOB_PAGE_SIZE
+}
+
+pub fn get_pages_amount_after_append(
+    start_page_no: usize,
+    data_len: usize,
+) -> Result<usize, PageBlobError> {
     let data_len_in_pages = data_len / BLOB_PAGE_SIZE;
-    return start_page_no + data_len_in_pages;
+    return get_end_page_no(start_page_no, data_len_in_pages);
 }
 
+// Saturates, the result is clamped to the max blob size by the callers
 pub fn get_ressize_to_pages_amount(pages_amount_needs: usize, pages_resize_ratio: usize) -> usize {
-    let full_pages_amount = (pages_amount_needs - 1) / pages_resize_ratio + 1;
+    let full_pages_amount = pages_amount_needs.div_ceil(pages_resize_ratio);
 
-    return full_pages_amount * pages_resize_ratio;
+    return full_pages_amount.saturating_mul(pages_resize_ratio);
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
     use super::*;
 
     #[test]
     fn get_full_page_ressize() {
+        assert_eq!(0, get_full_pages_size(0));
         assert_eq!(512, get_full_pages_size(1));
         assert_eq!(512, get_full_pages_size(512));
         assert_eq!(1024, get_full_pages_size(513));
@@ -371,22 +1200,163 @@ mod tests {
 
     #[test]
     fn test_ressize_payload_to_full_page() {
-        let mut payload: Vec<u8> = Vec::new();
+        let payload = ressize_payload_to_fullpage(Bytes::from(vec![1u8]));
 
-        payload.push(1);
+        assert_eq!(BLOB_PAGE_SIZE, payload.len());
+        assert_eq!(1, payload[0]);
+        assert_eq!(0, payload[BLOB_PAGE_SIZE - 1]);
 
-        ressize_payload_to_fullpage(&mut payload);
+        let full_page = Bytes::from(vec![1u8; BLOB_PAGE_SIZE]);
+        let payload = ressize_payload_to_fullpage(full_page.clone());
 
-        assert_eq!(BLOB_PAGE_SIZE, payload.len());
+        assert_eq!(full_page.as_ptr(), payload.as_ptr());
+    }
+
+    #[test]
+    fn test_ressize_empty_payload_to_full_page() {
+        let payload = ressize_payload_to_fullpage(Bytes::new());
+
+        assert_eq!(0, payload.len());
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_is_retried() {
+        let options = PageBlobOptions {
+            retries: 2,
+            retry_delay: Duration::from_millis(1),
+            ..Default::default()
+        };
+
+        let attempts = AtomicUsize::new(0);
+
+        let result = execute_round_trip(&options, || async {
+            if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                return Err(PageBlobError::ServerError {
+                    status: 503,
+                    code: "ServerBusy".to_string(),
+                });
+            }
+
+            Ok(5)
+        })
+        .await;
+
+        assert_eq!(5, result.unwrap());
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+
+        let attempts = AtomicUsize::new(0);
+
+        let result: Result<(), PageBlobError> = execute_round_trip(&options, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(AzureStorageError::BlobNotFound)
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(1, attempts.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_body_is_copied_only_for_retries() {
+        let body = vec![1u8; BLOB_PAGE_SIZE];
+        let body_ptr = body.as_ptr() as usize;
+
+        let sent_ptrs = std::sync::Mutex::new(Vec::new());
+
+        let result = execute_round_trip_with_body(&PageBlobOptions::default(), body, |body| {
+            sent_ptrs.lock().unwrap().push(body.as_ptr() as usize);
+            async { Ok::<_, PageBlobError>(()) }
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(vec![body_ptr], *sent_ptrs.lock().unwrap());
+
+        let options = PageBlobOptions {
+            retries: 2,
+            retry_delay: Duration::from_millis(1),
+            ..Default::default()
+        };
+
+        let body = vec![1u8; BLOB_PAGE_SIZE];
+        let body_ptr = body.as_ptr() as usize;
+
+        let sent_bodies = std::sync::Mutex::new(Vec::new());
+
+        let result: Result<(), PageBlobError> =
+            execute_round_trip_with_body(&options, body, |body| {
+                sent_bodies
+                    .lock()
+                    .unwrap()
+                    .push((body.as_ptr() as usize, body.len()));
+
+                async {
+                    Err(PageBlobError::ServerError {
+                        status: 503,
+                        code: "ServerBusy".to_string(),
+                    })
+                }
+            })
+            .await;
+
+        assert!(result.is_err());
+
+        let sent_bodies = sent_bodies.lock().unwrap();
+        assert_eq!(3, sent_bodies.len());
+        assert!(sent_bodies.iter().all(|(_, len)| *len == BLOB_PAGE_SIZE));
+        assert_eq!(body_ptr, sent_bodies[2].0);
+    }
+
+    #[test]
+    fn test_is_retriable() {
+        assert!(is_retriable(&PageBlobError::Timeout {
+            operation: "request".to_string(),
+            timeout: Duration::from_secs(1),
+            bytes_written: None,
+        }));
+        assert!(is_retriable(
+            &std::io::Error::other("Connection reset").into()
+        ));
+        assert!(is_retriable(&PageBlobError::ServerError {
+            status: 500,
+            code: "InternalError".to_string(),
+        }));
+        assert!(is_retriable(
+            &AzureStorageError::UnknownError {
+                msg: "<Error><Code>ServerBusy</Code></Error>".to_string(),
+            }
+            .into()
+        ));
+
+        assert!(!is_retriable(&PageBlobError::PreconditionFailed {
+            msg: "ConditionNotMet".to_string(),
+        }));
+        assert!(!is_retriable(&PageBlobError::ServerError {
+            status: 501,
+            code: "NotImplemented".to_string(),
+        }));
+        assert!(!is_retriable(
+            &AzureStorageError::UnknownError {
+                msg: "<Error><Code>AuthenticationFailed</Code></Error>".to_string(),
+            }
+            .into()
+        ));
+        assert!(!is_retriable(&AzureStorageError::BlobNotFound.into()));
     }
 
     #[test]
     fn test_get_pages_amount_after_append() {
-        assert_eq!(3, get_pages_amount_after_append(2, 512));
+        assert_eq!(3, get_pages_amount_after_append(2, 512).unwrap());
+        assert!(matches!(
+            get_pages_amount_after_append(usize::MAX, 512),
+            Err(PageBlobError::BlobTooLarge { .. })
+        ));
     }
 
     #[test]
     fn test_new_blob_size_in_pages_by_2() {
+        assert_eq!(0, get_ressize_to_pages_amount(0, 2));
+
         let need_pages = 1;
         let pages_ratio = 2;
 
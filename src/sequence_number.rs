@@ -0,0 +1,93 @@
+use crate::PageBlobError;
+
+pub const MAX_SEQUENCE_NUMBER: u64 = i64::MAX as u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceNumberAction {
+    Update(u64),
+    Increment,
+    Max(u64),
+}
+
+impl SequenceNumberAction {
+    pub fn apply(&self, current: u64) -> Result<u64, PageBlobError> {
+        let result = match self {
+            SequenceNumberAction::Update(value) => *value,
+            SequenceNumberAction::Increment => current + 1,
+            SequenceNumberAction::Max(value) => current.max(*value),
+        };
+
+        if result > MAX_SEQUENCE_NUMBER {
+            return Err(PageBlobError::SequenceNumberTooLarge {
+                value: result,
+                max_value: MAX_SEQUENCE_NUMBER,
+            });
+        }
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceNumberCondition {
+    LessOrEqual(u64),
+    LessThan(u64),
+    Equal(u64),
+}
+
+impl SequenceNumberCondition {
+    pub fn is_satisfied(&self, current: u64) -> bool {
+        match self {
+            SequenceNumberCondition::LessOrEqual(value) => current <= *value,
+            SequenceNumberCondition::LessThan(value) => current < *value,
+            SequenceNumberCondition::Equal(value) => current == *value,
+        }
+    }
+
+    pub fn check(&self, current: u64) -> Result<(), PageBlobError> {
+        if self.is_satisfied(current) {
+            return Ok(());
+        }
+
+        Err(PageBlobError::PreconditionFailed {
+            msg: format!(
+                "Sequence number condition {:?} failed. Blob sequence number is: {}",
+                self, current
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence_number_actions() {
+        assert_eq!(5, SequenceNumberAction::Update(5).apply(10).unwrap());
+        assert_eq!(11, SequenceNumberAction::Increment.apply(10).unwrap());
+        assert_eq!(10, SequenceNumberAction::Max(5).apply(10).unwrap());
+        assert_eq!(15, SequenceNumberAction::Max(15).apply(10).unwrap());
+
+        assert!(matches!(
+            SequenceNumberAction::Increment.apply(MAX_SEQUENCE_NUMBER),
+            Err(PageBlobError::SequenceNumberTooLarge { .. })
+        ));
+        assert!(matches!(
+            SequenceNumberAction::Update(MAX_SEQUENCE_NUMBER + 1).apply(0),
+            Err(PageBlobError::SequenceNumberTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sequence_number_conditions() {
+        assert!(SequenceNumberCondition::LessOrEqual(5).is_satisfied(5));
+        assert!(!SequenceNumberCondition::LessOrEqual(5).is_satisfied(6));
+
+        assert!(SequenceNumberCondition::LessThan(5).is_satisfied(4));
+        assert!(!SequenceNumberCondition::LessThan(5).is_satisfied(5));
+
+        assert!(SequenceNumberCondition::Equal(5).is_satisfied(5));
+        assert!(!SequenceNumberCondition::Equal(5).is_satisfied(4));
+    }
+}
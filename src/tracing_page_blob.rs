@@ -0,0 +1,465 @@
+use std::{collections::BTreeMap, future::Future, ops::Range, time::Instant};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use my_azure_storage_sdk::blob::BlobProperties;
+use tracing::{field::Empty, Instrument, Span};
+
+use crate::{
+    error_kind::get_error_kind, GrowthStrategy, MyPageBlob, PageBlobError, PageBlobOptions,
+    PageRangeResult, SequenceNumberAction, SequenceNumberCondition, WriteProgress,
+};
+
+pub struct TracingPageBlob<TMyPageBlob: MyPageBlob + Send + Sync> {
+    inner: TMyPageBlob,
+}
+
+impl<TMyPageBlob: MyPageBlob + Send + Sync> TracingPageBlob<TMyPageBlob> {
+    pub fn new(inner: TMyPageBlob) -> Self {
+        Self { inner }
+    }
+
+    pub fn into_inner(self) -> TMyPageBlob {
+        self.inner
+    }
+
+    fn create_span(&self, operation: &'static str) -> Span {
+        tracing::info_span!(
+            "page_blob",
+            operation,
+            container = self.inner.get_container_name(),
+            blob = self.inner.get_blob_name(),
+            start_page_no = Empty,
+            pages = Empty,
+            bytes = Empty,
+            duration_ms = Empty,
+            error_kind = Empty,
+        )
+    }
+}
+
+async fn trace_operation<TResult, TFuture: Future<Output = Result<TResult, PageBlobError>>>(
+    span: &Span,
+    operation: TFuture,
+) -> Result<TResult, PageBlobError> {
+    let started = Instant::now();
+
+    let result = operation.instrument(span.clone()).await;
+
+    span.record("duration_ms", started.elapsed().as_millis() as u64);
+
+    if let Err(err) = &result {
+        span.record("error_kind", get_error_kind(err));
+    }
+
+    result
+}
+
+#[async_trait]
+impl<TMyPageBlob: MyPageBlob + Send + Sync> MyPageBlob for TracingPageBlob<TMyPageBlob> {
+    fn get_container_name(&self) -> &str {
+        return self.inner.get_container_name();
+    }
+
+    fn get_blob_name(&self) -> &str {
+        return self.inner.get_blob_name();
+    }
+
+    fn get_options(&self) -> &PageBlobOptions {
+        return self.inner.get_options();
+    }
+
+    async fn get_blob_properties(&mut self) -> Result<BlobProperties, PageBlobError> {
+        let span = self.create_span("get_blob_properties");
+        let result = trace_operation(&span, self.inner.get_blob_properties()).await;
+
+        if let Ok(props) = &result {
+            span.record("bytes", props.blob_size);
+        }
+
+        result
+    }
+
+    async fn create(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        let span = self.create_span("create");
+        span.record("pages", pages_amount);
+        return trace_operation(&span, self.inner.create(pages_amount)).await;
+    }
+
+    async fn create_if_not_exists(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        let span = self.create_span("create_if_not_exists");
+        span.record("pages", pages_amount);
+        return trace_operation(&span, self.inner.create_if_not_exists(pages_amount)).await;
+    }
+
+    async fn get_available_pages_amount(&mut self) -> Result<usize, PageBlobError> {
+        let span = self.create_span("get_available_pages_amount");
+        let result = trace_operation(&span, self.inner.get_available_pages_amount()).await;
+
+        if let Ok(pages_amount) = &result {
+            span.record("pages", *pages_amount);
+        }
+
+        result
+    }
+
+    async fn refresh_size(&mut self) -> Result<usize, PageBlobError> {
+        let span = self.create_span("refresh_size");
+        let result = trace_operation(&span, self.inner.refresh_size()).await;
+
+        if let Ok(pages_amount) = &result {
+            span.record("pages", *pages_amount);
+        }
+
+        result
+    }
+
+    async fn create_container_if_not_exist(&mut self) -> Result<(), PageBlobError> {
+        let span = self.create_span("create_container_if_not_exist");
+        return trace_operation(&span, self.inner.create_container_if_not_exist()).await;
+    }
+
+    async fn resize(&mut self, pages_amount: usize) -> Result<(), PageBlobError> {
+        let span = self.create_span("resize");
+        span.record("pages", pages_amount);
+        return trace_operation(&span, self.inner.resize(pages_amount)).await;
+    }
+
+    async fn delete(&mut self) -> Result<(), PageBlobError> {
+        let span = self.create_span("delete");
+        return trace_operation(&span, self.inner.delete()).await;
+    }
+
+    async fn delete_if_exists(&mut self) -> Result<(), PageBlobError> {
+        let span = self.create_span("delete_if_exists");
+        return trace_operation(&span, self.inner.delete_if_exists()).await;
+    }
+
+    async fn get(
+        &mut self,
+        start_page_no: usize,
+        pages_amount: usize,
+    ) -> Result<Bytes, PageBlobError> {
+        let span = self.create_span("get");
+        span.record("start_page_no", start_page_no);
+        span.record("pages", pages_amount);
+
+        let result = trace_operation(&span, self.inner.get(start_page_no, pages_amount)).await;
+
+        if let Ok(payload) = &result {
+            span.record("bytes", payload.len());
+        }
+
+        result
+    }
+
+    async fn get_page_ranges_batch(
+        &mut self,
+        ranges: &[(usize, usize)],
+    ) -> Result<Vec<Bytes>, PageBlobError> {
+        let span = self.create_span("get_page_ranges_batch");
+        span.record(
+            "pages",
+            ranges
+                .iter()
+                .map(|(_, pages_amount)| pages_amount)
+                .sum::<usize>(),
+        );
+
+        return trace_operation(&span, self.inner.get_page_ranges_batch(ranges)).await;
+    }
+
+    async fn save_pages(
+        &mut self,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+    ) -> Result<usize, PageBlobError> {
+        let span = self.create_span("save_pages");
+        span.record("start_page_no", start_page_no);
+        span.record("bytes", payload.len());
+
+        return trace_operation(
+            &span,
+            self.inner
+                .save_pages(start_page_no, max_pages_to_write, payload),
+        )
+        .await;
+    }
+
+    async fn save_pages_with_progress(
+        &mut self,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+        write_progress: WriteProgress,
+    ) -> Result<usize, PageBlobError> {
+        let span = self.create_span("save_pages_with_progress");
+        span.record("start_page_no", start_page_no);
+        span.record("bytes", payload.len());
+
+        return trace_operation(
+            &span,
+            self.inner.save_pages_with_progress(
+                start_page_no,
+                max_pages_to_write,
+                payload,
+                write_progress,
+            ),
+        )
+        .await;
+    }
+
+    async fn auto_ressize_and_save_pages_with_growth(
+        &mut self,
+        start_page_no: usize,
+        max_pages_to_write_single_round_trip: usize,
+        payload: Bytes,
+        growth_strategy: &dyn GrowthStrategy,
+    ) -> Result<usize, PageBlobError> {
+        let span = self.create_span("auto_ressize_and_save_pages");
+        span.record("start_page_no", start_page_no);
+        span.record("bytes", payload.len());
+
+        return trace_operation(
+            &span,
+            self.inner.auto_ressize_and_save_pages_with_growth(
+                start_page_no,
+                max_pages_to_write_single_round_trip,
+                payload,
+                growth_strategy,
+            ),
+        )
+        .await;
+    }
+
+    async fn save_page_ranges(
+        &mut self,
+        ranges: Vec<(usize, Bytes)>,
+    ) -> Result<Vec<PageRangeResult>, PageBlobError> {
+        let span = self.create_span("save_page_ranges");
+        span.record(
+            "bytes",
+            ranges
+                .iter()
+                .map(|(_, payload)| payload.len())
+                .sum::<usize>(),
+        );
+
+        return trace_operation(&span, self.inner.save_page_ranges(ranges)).await;
+    }
+
+    async fn download(&mut self) -> Result<Bytes, PageBlobError> {
+        let span = self.create_span("download");
+        let result = trace_operation(&span, self.inner.download()).await;
+
+        if let Ok(payload) = &result {
+            span.record("bytes", payload.len());
+        }
+
+        result
+    }
+
+    async fn get_sequence_number(&mut self) -> Result<u64, PageBlobError> {
+        let span = self.create_span("get_sequence_number");
+        return trace_operation(&span, self.inner.get_sequence_number()).await;
+    }
+
+    async fn update_sequence_number(
+        &mut self,
+        action: SequenceNumberAction,
+    ) -> Result<u64, PageBlobError> {
+        let span = self.create_span("update_sequence_number");
+        return trace_operation(&span, self.inner.update_sequence_number(action)).await;
+    }
+
+    async fn resize_if_sequence_number(
+        &mut self,
+        condition: SequenceNumberCondition,
+        pages_amount: usize,
+    ) -> Result<(), PageBlobError> {
+        let span = self.create_span("resize_if_sequence_number");
+        span.record("pages", pages_amount);
+        return trace_operation(
+            &span,
+            self.inner
+                .resize_if_sequence_number(condition, pages_amount),
+        )
+        .await;
+    }
+
+    async fn save_pages_if_sequence_number(
+        &mut self,
+        condition: SequenceNumberCondition,
+        start_page_no: usize,
+        max_pages_to_write: usize,
+        payload: Bytes,
+    ) -> Result<usize, PageBlobError> {
+        let span = self.create_span("save_pages_if_sequence_number");
+        span.record("start_page_no", start_page_no);
+        span.record("bytes", payload.len());
+
+        return trace_operation(
+            &span,
+            self.inner.save_pages_if_sequence_number(
+                condition,
+                start_page_no,
+                max_pages_to_write,
+                payload,
+            ),
+        )
+        .await;
+    }
+
+    async fn get_written_page_ranges(&mut self) -> Result<Vec<Range<usize>>, PageBlobError> {
+        let span = self.create_span("get_written_page_ranges");
+        return trace_operation(&span, self.inner.get_written_page_ranges()).await;
+    }
+
+    async fn get_metadata(&mut self) -> Result<BTreeMap<String, String>, PageBlobError> {
+        let span = self.create_span("get_metadata");
+        return trace_operation(&span, self.inner.get_metadata()).await;
+    }
+
+    async fn set_metadata(
+        &mut self,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<(), PageBlobError> {
+        let span = self.create_span("set_metadata");
+        return trace_operation(&span, self.inner.set_metadata(metadata)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fmt::Debug,
+        sync::{Arc, Mutex},
+    };
+
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+
+    use super::*;
+    use crate::MyPageBlobMock;
+
+    type CapturedSpans = Arc<Mutex<Vec<(&'static str, BTreeMap<String, String>)>>>;
+
+    struct FieldsVisitor<'s>(&'s mut BTreeMap<String, String>);
+
+    impl Visit for FieldsVisitor<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    // Keeps the fields of every span, including the ones recorded after the span is created
+    struct CapturingSubscriber {
+        spans: CapturedSpans,
+    }
+
+    impl Subscriber for CapturingSubscriber {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = BTreeMap::new();
+            span.record(&mut FieldsVisitor(&mut fields));
+
+            let mut spans = self.spans.lock().unwrap();
+            spans.push((span.metadata().name(), fields));
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            let index = span.into_u64() as usize - 1;
+            values.record(&mut FieldsVisitor(&mut spans[index].1));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[tokio::test]
+    async fn test_emits_page_blob_spans() {
+        let spans = CapturedSpans::default();
+        let _guard = tracing::subscriber::set_default(CapturingSubscriber {
+            spans: spans.clone(),
+        });
+
+        let mut blob = TracingPageBlob::new(MyPageBlobMock::new());
+
+        blob.create_container_if_not_exist().await.unwrap();
+        blob.create(2).await.unwrap();
+        blob.save_pages(1, 10, vec![3u8; 512].into()).await.unwrap();
+        assert!(blob.get(1, 4).await.is_err());
+
+        let spans = spans.lock().unwrap();
+        let operations: Vec<&BTreeMap<String, String>> = spans
+            .iter()
+            .filter(|(name, _)| *name == "page_blob")
+            .map(|(_, fields)| fields)
+            .collect();
+
+        let get_field = |operation: &str, field: &str| {
+            operations
+                .iter()
+                .find(|fields| fields.get("operation").map(String::as_str) == Some(operation))
+                .and_then(|fields| fields.get(field).cloned())
+        };
+
+        assert_eq!(4, operations.len());
+
+        for fields in &operations {
+            assert_eq!(
+                Some("Mock_CONTAINER"),
+                fields.get("container").map(String::as_str)
+            );
+            assert_eq!(Some("Mock_BLOB"), fields.get("blob").map(String::as_str));
+            assert!(fields.contains_key("duration_ms"));
+        }
+
+        assert_eq!(Some("2".to_string()), get_field("create", "pages"));
+        assert_eq!(None, get_field("create", "error_kind"));
+
+        assert_eq!(
+            Some("1".to_string()),
+            get_field("save_pages", "start_page_no")
+        );
+        assert_eq!(Some("512".to_string()), get_field("save_pages", "bytes"));
+
+        assert_eq!(Some("4".to_string()), get_field("get", "pages"));
+        assert_eq!(
+            Some("OutOfRange".to_string()),
+            get_field("get", "error_kind")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forwards_to_inner_blob() {
+        let mut blob = TracingPageBlob::new(MyPageBlobMock::new());
+
+        blob.create_container_if_not_exist().await.unwrap();
+        blob.create(1).await.unwrap();
+        blob.save_pages(0, 10, vec![3u8; 512].into()).await.unwrap();
+
+        assert_eq!(vec![3u8; 512], blob.get(0, 1).await.unwrap());
+        assert!(blob.get_sequence_number().await.is_ok());
+        assert_eq!(1, blob.into_inner().pages.len());
+    }
+}
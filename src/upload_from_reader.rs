@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+
+use bytes::Bytes;
+use my_azure_storage_sdk::{page_blob::consts::BLOB_PAGE_SIZE, AzureStorageError};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{FixedMultipleGrowth, MyPageBlob, PageBlobError, MAX_PAGES_TO_WRITE};
+
+// Metadata entry with the bytes uploaded before the last page was padded
+pub const LOGICAL_LENGTH_METADATA_NAME: &str = "logical_length";
+
+#[derive(Debug, Clone)]
+pub struct UploadOptions {
+    // Pages read from the reader before they are written. Bounds the memory used by the upload
+    pub chunk_pages: usize,
+    // Falls back to resize_pages_ratio of the page blob options
+    pub resize_pages_ratio: Option<usize>,
+    // Otherwise the upload fails with PreconditionFailed if the blob exists. An existing blob is
+    // written over in place and trimmed after the reader is finished, so a failed upload leaves
+    // the old content with its beginning replaced rather than an empty blob
+    pub overwrite: bool,
+}
+
+impl Default for UploadOptions {
+    fn default() -> Self {
+        Self {
+            chunk_pages: MAX_PAGES_TO_WRITE,
+            resize_pages_ratio: None,
+            overwrite: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadResult {
+    // Bytes read from the reader. The last page is padded with zeros
+    pub logical_length: usize,
+    pub pages_amount: usize,
+}
+
+// Creates the page blob and fills it with the content of the reader. The blob grows by the resize ratio
+// while the content is written, is trimmed to the exact pages amount at the end and gets the logical length
+// as metadata. An existing blob is replaced only with the overwrite option
+pub async fn upload_from_reader<TMyPageBlob: MyPageBlob + Send, TReader: AsyncRead + Unpin>(
+    page_blob: &mut TMyPageBlob,
+    reader: &mut TReader,
+    options: UploadOptions,
+) -> Result<UploadResult, PageBlobError> {
+    let chunk_size = options.chunk_pages.clamp(1, MAX_PAGES_TO_WRITE) * BLOB_PAGE_SIZE;
+    let max_pages_to_write = page_blob.get_options().max_pages_to_write;
+    let growth_strategy = FixedMultipleGrowth::new(
+        options
+            .resize_pages_ratio
+            .unwrap_or(page_blob.get_options().resize_pages_ratio),
+    );
+
+    if options.overwrite {
+        page_blob.create_if_not_exists(0).await?;
+    } else {
+        check_blob_does_not_exist(page_blob).await?;
+        page_blob.create(0).await?;
+    }
+
+    let mut logical_length = 0;
+
+    loop {
+        let chunk = read_chunk(reader, chunk_size).await?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        let chunk_len = chunk.len();
+
+        page_blob
+            .auto_ressize_and_save_pages_with_growth(
+                logical_length / BLOB_PAGE_SIZE,
+                max_pages_to_write,
+                chunk,
+                &growth_strategy,
+            )
+            .await?;
+
+        logical_length += chunk_len;
+
+        if chunk_len < chunk_size {
+            break;
+        }
+    }
+
+    let pages_amount = logical_length.div_ceil(BLOB_PAGE_SIZE);
+
+    page_blob.resize(pages_amount).await?;
+
+    page_blob
+        .set_metadata(BTreeMap::from([(
+            LOGICAL_LENGTH_METADATA_NAME.to_string(),
+            logical_length.to_string(),
+        )]))
+        .await?;
+
+    Ok(UploadResult {
+        logical_length,
+        pages_amount,
+    })
+}
+
+// None if the blob is not uploaded by upload_from_reader
+pub async fn get_logical_length<TMyPageBlob: MyPageBlob + Send>(
+    page_blob: &mut TMyPageBlob,
+) -> Result<Option<usize>, PageBlobError> {
+    let metadata = page_blob.get_metadata().await?;
+
+    let value = match metadata.get(LOGICAL_LENGTH_METADATA_NAME) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    match value.parse() {
+        Ok(result) => Ok(Some(result)),
+        Err(_) => Err(PageBlobError::Corrupted {
+            msg: format!("Invalid logical length {}", value),
+        }),
+    }
+}
+
+// The blob can still be created by another writer between the check and the create
+pub(crate) async fn check_blob_does_not_exist<TMyPageBlob: MyPageBlob + Send>(
+    page_blob: &mut TMyPageBlob,
+) -> Result<(), PageBlobError> {
+    match page_blob.get_blob_properties().await {
+        Ok(_) => Err(PageBlobError::PreconditionFailed {
+            msg: format!(
+                "Blob {}/{} already exists",
+                page_blob.get_container_name(),
+                page_blob.get_blob_name()
+            ),
+        }),
+        Err(PageBlobError::Azure(AzureStorageError::BlobNotFound)) => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+// Reads until the chunk is full or the reader is finished
+async fn read_chunk<TReader: AsyncRead + Unpin>(
+    reader: &mut TReader,
+    chunk_size: usize,
+) -> Result<Bytes, PageBlobError> {
+    let mut chunk = vec![0u8; chunk_size];
+    let mut filled = 0;
+
+    while filled < chunk_size {
+        let read = reader.read(&mut chunk[filled..]).await?;
+
+        if read == 0 {
+            break;
+        }
+
+        filled += read;
+    }
+
+    chunk.truncate(filled);
+
+    Ok(chunk.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MyPageBlobMock;
+
+    #[tokio::test]
+    async fn test_upload_from_reader() {
+        let mut page_blob = MyPageBlobMock::new();
+        page_blob.create_container_if_not_exist().await.unwrap();
+        page_blob.create(10).await.unwrap();
+        page_blob
+            .save_pages(0, 10, vec![0xFF; 10 * BLOB_PAGE_SIZE].into())
+            .await
+            .unwrap();
+
+        let payload: Vec<u8> = (0..5 * BLOB_PAGE_SIZE / 2).map(|i| i as u8).collect();
+
+        let result = upload_from_reader(
+            &mut page_blob,
+            &mut payload.as_slice(),
+            UploadOptions {
+                chunk_pages: 1,
+                resize_pages_ratio: Some(4),
+                overwrite: true,
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            UploadResult {
+                logical_length: payload.len(),
+                pages_amount: 3
+            },
+            result
+        );
+
+        assert_eq!(3, page_blob.get_available_pages_amount().await.unwrap());
+        assert_eq!(
+            Some(payload.len()),
+            get_logical_length(&mut page_blob).await.unwrap()
+        );
+
+        let downloaded = page_blob.download().await.unwrap();
+        assert_eq!(payload, downloaded[..payload.len()]);
+        assert_eq!(vec![0u8; BLOB_PAGE_SIZE / 2], downloaded[payload.len()..]);
+    }
+
+    #[tokio::test]
+    async fn test_upload_from_empty_reader() {
+        let mut page_blob = MyPageBlobMock::new();
+        page_blob.create_container_if_not_exist().await.unwrap();
+
+        let result = upload_from_reader(
+            &mut page_blob,
+            &mut tokio::io::empty(),
+            UploadOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(0, result.pages_amount);
+        assert_eq!(0, page_blob.get_available_pages_amount().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_upload_does_not_overwrite_by_default() {
+        let mut page_blob = MyPageBlobMock::new();
+        page_blob.create_container_if_not_exist().await.unwrap();
+        page_blob.create(1).await.unwrap();
+        page_blob
+            .save_pages(0, 1, vec![1u8; BLOB_PAGE_SIZE].into())
+            .await
+            .unwrap();
+
+        let result = upload_from_reader(
+            &mut page_blob,
+            &mut [2u8; 10].as_slice(),
+            UploadOptions::default(),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(PageBlobError::PreconditionFailed { .. })
+        ));
+        assert_eq!(
+            vec![1u8; BLOB_PAGE_SIZE],
+            page_blob.download().await.unwrap()
+        );
+        assert_eq!(None, get_logical_length(&mut page_blob).await.unwrap());
+    }
+}
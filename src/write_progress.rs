@@ -0,0 +1,62 @@
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use my_azure_storage_sdk::page_blob::consts::BLOB_PAGE_SIZE;
+
+// Shared with the caller so the amount of committed bytes is still known
+// if the write future is dropped or timed out in the middle of a chunked write
+#[derive(Debug, Clone, Default)]
+pub struct WriteProgress {
+    bytes_written: Arc<AtomicUsize>,
+    failed_pages: Arc<Mutex<Option<Range<usize>>>>,
+}
+
+impl WriteProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_bytes_written(&self) -> usize {
+        self.bytes_written.load(Ordering::SeqCst)
+    }
+
+    // Pages committed from the start page of the write. Pages after them are not guaranteed to be written
+    pub fn get_pages_written(&self) -> usize {
+        self.get_bytes_written() / BLOB_PAGE_SIZE
+    }
+
+    pub fn get_failed_pages(&self) -> Option<Range<usize>> {
+        self.failed_pages.lock().unwrap().clone()
+    }
+
+    pub(crate) fn add_bytes_written(&self, bytes_written: usize) {
+        self.bytes_written
+            .fetch_add(bytes_written, Ordering::SeqCst);
+    }
+
+    pub(crate) fn set_failed_pages(&self, failed_pages: Range<usize>) {
+        *self.failed_pages.lock().unwrap() = Some(failed_pages);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_is_shared_between_clones() {
+        let write_progress = WriteProgress::new();
+        let writer_progress = write_progress.clone();
+
+        writer_progress.add_bytes_written(BLOB_PAGE_SIZE * 4);
+        writer_progress.set_failed_pages(14..18);
+
+        assert_eq!(4, write_progress.get_pages_written());
+        assert_eq!(Some(14..18), write_progress.get_failed_pages());
+    }
+}